@@ -0,0 +1,206 @@
+//! RPC interface for the AssetTracking pallet.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+pub use pallet_template_runtime_api::AssetTrackingApi as AssetTrackingRuntimeApi;
+use pallet_template_runtime_api::{EdgeInfo, EmissionsReport, EtaInfo, RouteObjective, RouteQuote, ShipmentInfo, ShipmentRole};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+#[rpc]
+pub trait AssetTrackingApi<BlockHash, AccountId, Balance, BlockNumber> {
+	#[rpc(name = "assetTracking_shipment")]
+	fn shipment(
+		&self,
+		uid: u64,
+		at: Option<BlockHash>,
+	) -> Result<Option<ShipmentInfo<AccountId, Balance, BlockNumber>>>;
+
+	#[rpc(name = "assetTracking_shipmentsHeldBy")]
+	fn shipments_held_by(&self, account: AccountId, at: Option<BlockHash>) -> Result<Vec<u64>>;
+
+	#[rpc(name = "assetTracking_quoteRoute")]
+	fn quote_route(
+		&self,
+		origin: AccountId,
+		destination: AccountId,
+		objective: RouteObjective,
+		at: Option<BlockHash>,
+	) -> Result<Option<RouteQuote<AccountId, Balance, BlockNumber>>>;
+
+	#[rpc(name = "assetTracking_transitNetwork")]
+	fn transit_network(
+		&self,
+		at: Option<BlockHash>,
+	) -> Result<Vec<(AccountId, Vec<(AccountId, EdgeInfo<BlockNumber>)>)>>;
+
+	#[rpc(name = "assetTracking_eta")]
+	fn eta(&self, uid: u64, at: Option<BlockHash>) -> Result<Option<EtaInfo<BlockNumber>>>;
+
+	#[rpc(name = "assetTracking_inboundShipments")]
+	fn inbound_shipments(&self, transit_node: AccountId, at: Option<BlockHash>) -> Result<Vec<(u64, BlockNumber)>>;
+
+	#[rpc(name = "assetTracking_shipmentsBy")]
+	fn shipments_by(
+		&self,
+		role: ShipmentRole,
+		account: AccountId,
+		start_after: Option<u64>,
+		limit: u32,
+		at: Option<BlockHash>,
+	) -> Result<Vec<u64>>;
+
+	#[rpc(name = "assetTracking_emissionsReport")]
+	fn emissions_report(
+		&self,
+		account: AccountId,
+		from: BlockNumber,
+		to: BlockNumber,
+		at: Option<BlockHash>,
+	) -> Result<EmissionsReport<BlockNumber>>;
+}
+
+/// Provides RPC methods to query the AssetTracking pallet.
+pub struct AssetTracking<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> AssetTracking<C, B> {
+	/// Create new `AssetTracking` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to runtime failed.
+	RuntimeError,
+}
+
+impl From<Error> for i64 {
+	fn from(e: Error) -> i64 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+fn runtime_error(message: &str, e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(Error::RuntimeError.into()),
+		message: message.into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<C, Block, AccountId, Balance, BlockNumber>
+	AssetTrackingApi<<Block as BlockT>::Hash, AccountId, Balance, BlockNumber> for AssetTracking<C, Block>
+where
+	Block: BlockT,
+	C: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: AssetTrackingRuntimeApi<Block, AccountId, Balance, BlockNumber>,
+	AccountId: Codec,
+	Balance: Codec,
+	BlockNumber: Codec,
+{
+	fn shipment(
+		&self,
+		uid: u64,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<ShipmentInfo<AccountId, Balance, BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.shipment(&at, uid).map_err(|e| runtime_error("Unable to query shipment.", e))
+	}
+
+	fn shipments_held_by(
+		&self,
+		account: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<u64>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.shipments_held_by(&at, account)
+			.map_err(|e| runtime_error("Unable to query shipments held by account.", e))
+	}
+
+	fn quote_route(
+		&self,
+		origin: AccountId,
+		destination: AccountId,
+		objective: RouteObjective,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<RouteQuote<AccountId, Balance, BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.quote_route(&at, origin, destination, objective)
+			.map_err(|e| runtime_error("Unable to quote route.", e))
+	}
+
+	fn transit_network(
+		&self,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<(AccountId, Vec<(AccountId, EdgeInfo<BlockNumber>)>)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.transit_network(&at).map_err(|e| runtime_error("Unable to query transit network.", e))
+	}
+
+	fn eta(&self, uid: u64, at: Option<<Block as BlockT>::Hash>) -> Result<Option<EtaInfo<BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.eta(&at, uid).map_err(|e| runtime_error("Unable to query shipment ETA.", e))
+	}
+
+	fn inbound_shipments(
+		&self,
+		transit_node: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<(u64, BlockNumber)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.inbound_shipments(&at, transit_node)
+			.map_err(|e| runtime_error("Unable to query inbound shipments.", e))
+	}
+
+	fn shipments_by(
+		&self,
+		role: ShipmentRole,
+		account: AccountId,
+		start_after: Option<u64>,
+		limit: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<u64>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.shipments_by(&at, role, account, start_after, limit)
+			.map_err(|e| runtime_error("Unable to query shipments by role.", e))
+	}
+
+	fn emissions_report(
+		&self,
+		account: AccountId,
+		from: BlockNumber,
+		to: BlockNumber,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<EmissionsReport<BlockNumber>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.emissions_report(&at, account, from, to)
+			.map_err(|e| runtime_error("Unable to produce emissions report.", e))
+	}
+}
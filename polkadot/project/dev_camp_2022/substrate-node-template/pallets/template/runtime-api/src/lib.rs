@@ -0,0 +1,179 @@
+//! Runtime API definition for the AssetTracking pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Decode, Encode};
+pub use pallet_template::{EdgeProfile, HandlingFlags, RouteObjective, ShipmentRole, ShipmentStatus, MAX_REPORT_BLOCKS, MAX_SHIPMENTS_PAGE};
+use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// A shipment as returned to clients, independent of the runtime's `Config`.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct ShipmentInfo<AccountId, Balance, BlockNumber> {
+	pub uid: u64,
+	pub creator: AccountId,
+	pub destination: AccountId,
+	pub route: Vec<AccountId>,
+	pub owner_index: u8,
+	pub status: ShipmentStatus,
+	pub fees: Option<Balance>,
+	pub leg_fees: Vec<Balance>,
+	pub leg_deadlines: Vec<BlockNumber>,
+	pub metadata: Option<MetadataInfo<Balance>>,
+	/// Objective the route was planned for.
+	pub objective: RouteObjective,
+	/// Metrics of the route as last planned, see `RouteQuote`.
+	pub cost: u64,
+	pub transit_time: BlockNumber,
+	/// Grams of CO₂ equivalent.
+	pub emissions: u64,
+}
+
+/// Description of the goods in a shipment.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct MetadataInfo<Balance> {
+	pub content: Vec<u8>,
+	pub mass: u32,
+	pub volume: u32,
+	pub declared_value: Balance,
+	pub flags: HandlingFlags,
+}
+
+/// Best route between two transit nodes for an objective and what shipping over it would
+/// cost, take and emit.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct RouteQuote<AccountId, Balance, BlockNumber> {
+	pub route: Vec<AccountId>,
+	pub cost: u64,
+	pub fees: Balance,
+	pub transit_time: BlockNumber,
+	/// Grams of CO₂ equivalent.
+	pub emissions: u64,
+}
+
+/// Emissions an account is accountable for over a block range, in grams of CO₂ equivalent.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct EmissionsReport<BlockNumber> {
+	/// Legs of the shipments the account created.
+	pub as_creator: u64,
+	/// Legs the account carried as a transit node.
+	pub as_carrier: u64,
+	/// Emissions per shipment uid, counting legs the account both created and carried once.
+	pub shipments: Vec<(u64, u64)>,
+	/// Last block covered, before the one asked for when the range is longer than
+	/// `MAX_REPORT_BLOCKS` or reaches past the current block.
+	pub to: BlockNumber,
+}
+
+/// Edge from one transit node to another.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct EdgeInfo<BlockNumber> {
+	pub cost: u32,
+	/// Expected transit time in blocks, `LegTimeout` when unset.
+	pub transit_time: Option<BlockNumber>,
+	pub profile: Option<EdgeProfile>,
+}
+
+/// Planned and currently expected arrival of an in-transit shipment.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct EtaInfo<BlockNumber> {
+	/// Block at which each hop after the first was planned to be reached.
+	pub planned_arrivals: Vec<BlockNumber>,
+	/// Block at which the destination is expected to be reached given the actual handoffs.
+	pub expected_arrival: BlockNumber,
+}
+
+sp_api::decl_runtime_apis! {
+	pub trait AssetTrackingApi<AccountId, Balance, BlockNumber> where
+		AccountId: Codec,
+		Balance: Codec,
+		BlockNumber: Codec,
+	{
+		/// Shipment stored under `uid`.
+		fn shipment(uid: u64) -> Option<ShipmentInfo<AccountId, Balance, BlockNumber>>;
+		/// Uids of the in-transit shipments currently in the custody of `account`.
+		fn shipments_held_by(account: AccountId) -> Vec<u64>;
+		/// Route and fees `create_shipment` would use between `origin` and `destination` for
+		/// `objective`.
+		fn quote_route(
+			origin: AccountId,
+			destination: AccountId,
+			objective: RouteObjective,
+		) -> Option<RouteQuote<AccountId, Balance, BlockNumber>>;
+		/// Every transit node with its outgoing edges.
+		fn transit_network() -> Vec<(AccountId, Vec<(AccountId, EdgeInfo<BlockNumber>)>)>;
+		/// ETA of the in-transit shipment stored under `uid`.
+		fn eta(uid: u64) -> Option<EtaInfo<BlockNumber>>;
+		/// Uids and planned arrivals of the shipments whose next hop is `transit_node`,
+		/// earliest first.
+		fn inbound_shipments(transit_node: AccountId) -> Vec<(u64, BlockNumber)>;
+		/// Up to `limit` uids of the shipments `account` relates to as `role`, at most
+		/// `MAX_SHIPMENTS_PAGE`, continuing the page that ended with `start_after`.
+		fn shipments_by(role: ShipmentRole, account: AccountId, start_after: Option<u64>, limit: u32) -> Vec<u64>;
+		/// Emissions of the legs completed between blocks `from` and `to` inclusive that
+		/// `account` is accountable for, covering at most `MAX_REPORT_BLOCKS` blocks. Shipments
+		/// already archived are left out.
+		fn emissions_report(account: AccountId, from: BlockNumber, to: BlockNumber) -> EmissionsReport<BlockNumber>;
+	}
+}
+
+impl<T: pallet_template::Config> From<pallet_template::Shipment<T>>
+	for ShipmentInfo<T::AccountId, pallet_template::BalanceOf<T>, T::BlockNumber>
+{
+	fn from(shipment: pallet_template::Shipment<T>) -> Self {
+		Self {
+			uid: shipment.uid,
+			creator: shipment.creator,
+			destination: shipment.destination,
+			route: shipment.route.into_inner(),
+			owner_index: shipment.owner_index,
+			status: shipment.status,
+			fees: shipment.fees,
+			leg_fees: shipment.leg_fees.into_inner(),
+			leg_deadlines: shipment.leg_deadlines.into_inner(),
+			// Stored apart from the shipment, see `MetadataInfo`
+			metadata: None,
+			objective: shipment.objective,
+			cost: shipment.metrics.cost,
+			transit_time: shipment.metrics.transit_time,
+			emissions: shipment.metrics.emissions,
+		}
+	}
+}
+
+impl<T: pallet_template::Config> From<pallet_template::ShipmentMetadata<T>> for MetadataInfo<pallet_template::BalanceOf<T>> {
+	fn from(metadata: pallet_template::ShipmentMetadata<T>) -> Self {
+		Self {
+			content: metadata.content.into_inner(),
+			mass: metadata.mass,
+			volume: metadata.volume,
+			declared_value: metadata.declared_value,
+			flags: metadata.flags,
+		}
+	}
+}
+
+impl<T: pallet_template::Config> From<pallet_template::EdgeAttributes<T>> for EdgeInfo<T::BlockNumber> {
+	fn from(edge: pallet_template::EdgeAttributes<T>) -> Self {
+		Self {
+			cost: edge.cost,
+			transit_time: edge.transit_time,
+			profile: edge.profile,
+		}
+	}
+}
@@ -3,18 +3,450 @@
 use super::*;
 
 #[allow(unused)]
-use crate::Pallet as Template;
-use frame_benchmarking::{benchmarks, whitelisted_caller};
-use frame_system::RawOrigin;
+use crate::Pallet as AssetTracking;
+use codec::Encode;
+use frame_benchmarking::{account, benchmarks};
+use frame_support::{
+	assert_ok,
+	traits::{Currency, Get},
+	BoundedVec,
+};
+use frame_system::{offchain::AppCrypto, RawOrigin};
+use sp_runtime::{
+	traits::{Bounded, IdentifyAccount, Saturating},
+	RuntimeAppPublic,
+};
+use sp_std::{vec, vec::Vec};
+
+const SEED: u32 = 0;
+
+fn assert_last_event<T: Config>(generic_event: <T as Config>::Event) {
+	frame_system::Pallet::<T>::assert_last_event(generic_event.into());
+}
+
+// Registers `n` transit nodes connected as a line, each to the previous one.
+fn create_line<T: Config>(n: u32) -> Vec<T::AccountId> {
+	let mut nodes = Vec::new();
+	for i in 0..n {
+		let node: T::AccountId = account("transit_node", i, SEED);
+		// Leg fees can only be paid out to existing accounts, which must also afford the bond
+		T::Currency::make_free_balance_be(&node, T::Currency::minimum_balance().saturating_add(T::TransitNodeBond::get()));
+		let neighbours: BoundedVec<(T::AccountId, u32), T::MaxSize> = match nodes.last() {
+			Some(previous) => vec![(previous.clone(), 1)].try_into().unwrap(),
+			None => Default::default(),
+		};
+		assert_ok!(AssetTracking::<T>::create_new_transit_node(T::NetworkAdminOrigin::successful_origin(), node.clone(), neighbours));
+		nodes.push(node);
+	}
+	nodes
+}
+
+// Registers a tree of `n` transit nodes rooted at the returned origin, and a destination only
+// reachable from the last of them over an expensive edge. Every node has `MaxEdgesPerNode`
+// outgoing edges, those the tree leaves over leading to sinks costlier than the destination,
+// so the route search settles the whole tree and the destination, `n + 1` labels, reading
+// every edge of each. Every edge weighs each attribute and every node has a capacity to check.
+fn create_search_tree<T: Config>(n: u32) -> (T::AccountId, T::AccountId, T::AccountId) {
+	let edge = |cost: u32| EdgeAttributes::<T> {
+		cost,
+		transit_time: Some(1u32.into()),
+		profile: Some(EdgeProfile { distance: 1, mode: TransportMode::Road }),
+	};
+	EmissionFactors::<T>::insert(TransportMode::Road, 1);
+
+	let max_edges = T::MaxEdgesPerNode::get();
+	let branching = max_edges.saturating_sub(1).max(1) as usize;
+	let nodes: Vec<T::AccountId> = (0..n.max(1)).map(|i| account("transit_node", i, SEED)).collect();
+	let destination: T::AccountId = account("destination", 0, SEED);
+	for (i, node) in nodes.iter().enumerate().skip(1) {
+		let parent = &nodes[(i - 1) / branching];
+		Edges::<T>::insert(parent, node, edge(1));
+		Edges::<T>::insert(node, parent, edge(1));
+	}
+	let last = nodes[nodes.len() - 1].clone();
+	Edges::<T>::insert(&last, &destination, edge(1_000));
+	Edges::<T>::insert(&destination, &last, edge(1_000));
+	for node in nodes.iter() {
+		let spare = max_edges.saturating_sub(Edges::<T>::iter_key_prefix(node).count() as u32);
+		for sink in 0..spare {
+			Edges::<T>::insert(node, account::<T::AccountId>("sink", sink, SEED), edge(1_000_000));
+		}
+		TransitNodes::<T>::insert(node, ());
+		Capacity::<T>::insert(node, u32::MAX);
+	}
+	TransitNodes::<T>::insert(&destination, ());
+
+	(nodes[0].clone(), last, destination)
+}
+
+// Ships from the first to the last node of a line of `n` nodes.
+fn create_shipment_over<T: Config>(n: u32) -> Vec<T::AccountId> {
+	let nodes = create_line::<T>(n);
+	let creator = nodes[0].clone();
+	T::Currency::make_free_balance_be(&creator, BalanceOf::<T>::max_value() / 2u32.into());
+	let commitment = AssetTracking::<T>::handoff_commitment(1, 1, &[1u8; 32]);
+	assert_ok!(AssetTracking::<T>::create_shipment(
+		RawOrigin::Signed(creator).into(),
+		nodes[n as usize - 1].clone(),
+		commitment,
+		Default::default(),
+		None,
+		None,
+		RouteObjective::Cheapest
+	));
+	nodes
+}
+
+// Delivers a shipment over a line of two nodes, leaving it awaiting acceptance.
+fn deliver_shipment<T: Config>() -> Vec<T::AccountId> {
+	let nodes = create_shipment_over::<T>(2);
+	assert_ok!(AssetTracking::<T>::update_shipment(
+		RawOrigin::Signed(nodes[1].clone()).into(), 1, [1u8; 32], None, Default::default(), None
+	));
+	nodes
+}
+
+// Limits breached by the default reading, reported by `account("sensor", 0, SEED)`.
+fn breachable_conditions<T: Config>() -> ConditionLimits<T> {
+	ConditionLimits {
+		min_temperature: 200,
+		max_temperature: 800,
+		min_humidity: 0,
+		max_humidity: 6_000,
+		penalty: T::FailureSlash::get(),
+		sensor: Some(account("sensor", 0, SEED)),
+	}
+}
+
+// Hands a shipment with `breachable_conditions` over to `holder`, the second of three nodes.
+fn hand_over_breachable<T: Config>(holder: &T::AccountId) {
+	let nodes = create_line::<T>(1);
+	let destination: T::AccountId = account("destination", 0, SEED);
+	for (node, neighbour) in [(holder, &nodes[0]), (&destination, holder)] {
+		T::Currency::make_free_balance_be(node, T::Currency::minimum_balance().saturating_add(T::TransitNodeBond::get()));
+		let neighbours: BoundedVec<(T::AccountId, u32), T::MaxSize> = vec![(neighbour.clone(), 1)].try_into().unwrap();
+		assert_ok!(AssetTracking::<T>::create_new_transit_node(T::NetworkAdminOrigin::successful_origin(), node.clone(), neighbours));
+	}
+	T::Currency::make_free_balance_be(&nodes[0], BalanceOf::<T>::max_value() / 2u32.into());
+	assert_ok!(AssetTracking::<T>::create_shipment(
+		RawOrigin::Signed(nodes[0].clone()).into(),
+		destination,
+		AssetTracking::<T>::handoff_commitment(1, 1, &[1u8; 32]),
+		Default::default(),
+		None,
+		Some(breachable_conditions::<T>()),
+		RouteObjective::Cheapest
+	));
+	let next_commitment = AssetTracking::<T>::handoff_commitment(1, 2, &[2u8; 32]);
+	assert_ok!(AssetTracking::<T>::update_shipment(
+		RawOrigin::Signed(holder.clone()).into(), 1, [1u8; 32], Some(next_commitment), Default::default(), None
+	));
+}
 
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
-		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+	create_new_transit_node {
+		let n in 0 .. T::MaxEdgesPerNode::get();
+		let neighbours: BoundedVec<(T::AccountId, u32), T::MaxSize> = create_line::<T>(n)
+			.into_iter()
+			.map(|node| (node, 1))
+			.collect::<Vec<_>>()
+			.try_into()
+			.unwrap();
+		let transit_node: T::AccountId = account("new_transit_node", 0, SEED);
+		let origin = T::NetworkAdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, transit_node.clone(), neighbours)
+	verify {
+		assert_last_event::<T>(Event::TransitPointCreated(transit_node).into());
+	}
+
+	update_neighbour {
+		let nodes = create_line::<T>(2);
+		let origin = T::NetworkAdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, nodes[0].clone(), nodes[1].clone(), 5)
+	verify {
+		assert_eq!(AssetTracking::<T>::edge(&nodes[1], &nodes[0]).map(|edge| edge.cost), Some(5));
+	}
+
+	// Removing one direction also drops its duration and profile
+	set_directed_edge {
+		let nodes = create_line::<T>(2);
+		let origin = T::NetworkAdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, nodes[1].clone(), nodes[0].clone(), None)
+	verify {
+		assert_eq!(AssetTracking::<T>::edge(&nodes[1], &nodes[0]).map(|edge| edge.cost), None);
+		assert_eq!(AssetTracking::<T>::edge(&nodes[0], &nodes[1]).map(|edge| edge.cost), Some(1));
+	}
+
+	set_edge_duration {
+		let nodes = create_line::<T>(2);
+		let duration: T::BlockNumber = 5u32.into();
+		let origin = T::NetworkAdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, nodes[0].clone(), nodes[1].clone(), Some(duration))
+	verify {
+		assert_eq!(AssetTracking::<T>::edge(&nodes[1], &nodes[0]).and_then(|edge| edge.transit_time), Some(duration));
+	}
+
+	set_edge_profile {
+		let nodes = create_line::<T>(2);
+		let profile = EdgeProfile { distance: 1_000, mode: TransportMode::Rail };
+		let origin = T::NetworkAdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, nodes[0].clone(), nodes[1].clone(), Some(profile))
+	verify {
+		assert_eq!(AssetTracking::<T>::edge(&nodes[1], &nodes[0]).and_then(|edge| edge.profile), Some(profile));
+	}
+
+	set_emission_factor {
+		let origin = T::NetworkAdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, TransportMode::Road, Some(60))
+	verify {
+		assert_eq!(AssetTracking::<T>::emission_factor(TransportMode::Road), 60);
+	}
+
+	remove_transit_node {
+		let n in 1 .. 100;
+		let nodes = create_line::<T>(n);
+		let transit_node = nodes[n as usize - 1].clone();
+		let origin = T::NetworkAdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, transit_node.clone())
+	verify {
+		assert_last_event::<T>(Event::TransitPointRemoved(transit_node).into());
+	}
+
+	// A shipment rerouted from a closed edge over a detour
+	reroute_shipment {
+		let nodes = create_shipment_over::<T>(3);
+		let detour: T::AccountId = account("detour", 0, SEED);
+		T::Currency::make_free_balance_be(&detour, T::Currency::minimum_balance().saturating_add(T::TransitNodeBond::get()));
+		let neighbours: BoundedVec<(T::AccountId, u32), T::MaxSize> =
+			vec![(nodes[0].clone(), 5), (nodes[2].clone(), 5)].try_into().unwrap();
+		assert_ok!(AssetTracking::<T>::create_new_transit_node(T::NetworkAdminOrigin::successful_origin(), detour.clone(), neighbours));
+		Edges::<T>::remove(&nodes[1], &nodes[2]);
+		RerouteQueue::<T>::insert(0, (NetworkChange::Edge(nodes[1].clone(), nodes[2].clone()), None));
+		RerouteHead::<T>::put(0);
+		RerouteTail::<T>::put(1);
+	}: {
+		AssetTracking::<T>::process_reroutes(1);
+	}
+	verify {
+		assert_eq!(AssetTracking::<T>::uid_to_shipment(1).unwrap().route.to_vec(), vec![nodes[0].clone(), detour, nodes[2].clone()]);
+	}
+
+	// Route search settling `i` labels, weighing every attribute. Dispatchables that search
+	// for a route add the search at `MaxRouteSearchIterations` to their own weight.
+	route_search {
+		let i in 2 .. T::MaxRouteSearchIterations::get();
+		let (origin, last, destination) = create_search_tree::<T>(i - 1);
+		let objective = RouteObjective::Weighted { cost: 1, transit_time: 1, emissions: 1 };
+	}: {
+		let (route, _, _) = AssetTracking::<T>::quote_route(&origin, &destination, &objective).unwrap();
+		assert_eq!(route[route.len() - 2..].to_vec(), vec![last.clone(), destination.clone()]);
+	}
+
+	// Shipment with every optional record, over a route too short for its search to count
+	create_shipment {
+		let nodes = create_line::<T>(3);
+		let creator = nodes[0].clone();
+		let destination = nodes[2].clone();
+		T::Currency::make_free_balance_be(&creator, BalanceOf::<T>::max_value() / 2u32.into());
+		let commitment = AssetTracking::<T>::handoff_commitment(1, 1, &[1u8; 32]);
+		let metadata = ShipmentMetadata::<T> {
+			content: vec![0u8; T::MaxContentIdLen::get() as usize].try_into().unwrap(),
+			mass: T::MaxMass::get(),
+			volume: T::MaxVolume::get(),
+			declared_value: T::MaxDeclaredValue::get(),
+			flags: HandlingFlags(HandlingFlags::ALL),
+		};
+		let conditions = breachable_conditions::<T>();
+		let objective = RouteObjective::Weighted { cost: 1, transit_time: 1, emissions: 1 };
+	}: _(RawOrigin::Signed(creator.clone()), destination.clone(), commitment, Default::default(), Some(metadata), Some(conditions), objective)
+	verify {
+		assert_eq!(AssetTracking::<T>::uid_to_shipment(1).unwrap().route.to_vec(), nodes);
+		assert!(AssetTracking::<T>::shipment_metadata(1).is_some());
+		assert!(AssetTracking::<T>::shipment_conditions(1).is_some());
+	}
+
+	// Intermediate handoff, which also schedules the next deadline and commitment
+	update_shipment {
+		let nodes = create_shipment_over::<T>(3);
+		let next_commitment = AssetTracking::<T>::handoff_commitment(1, 2, &[2u8; 32]);
+	}: _(RawOrigin::Signed(nodes[1].clone()), 1, [1u8; 32], Some(next_commitment), Default::default(), Some(T::Hash::default()))
+	verify {
+		assert_eq!(AssetTracking::<T>::shipment_commitment(1), Some(next_commitment));
+	}
+
+	accept_shipment {
+		let nodes = deliver_shipment::<T>();
+	}: _(RawOrigin::Signed(nodes[1].clone()), 1)
+	verify {
+		assert_last_event::<T>(Event::ShipmentAccepted(1, nodes[1].clone()).into());
+	}
+
+	reject_shipment {
+		let nodes = deliver_shipment::<T>();
+		let commitment = AssetTracking::<T>::handoff_commitment(2, 1, &[3u8; 32]);
+	}: _(RawOrigin::Signed(nodes[1].clone()), 1, RejectionReason::Damaged, commitment, Default::default())
+	verify {
+		assert_eq!(AssetTracking::<T>::return_of(2), Some(1));
+	}
+
+	// Cancelled after the first handoff, which pays the penalty to the holder
+	cancel_shipment {
+		let nodes = create_shipment_over::<T>(3);
+		let next_commitment = AssetTracking::<T>::handoff_commitment(1, 2, &[2u8; 32]);
+		assert_ok!(AssetTracking::<T>::update_shipment(
+			RawOrigin::Signed(nodes[1].clone()).into(), 1, [1u8; 32], Some(next_commitment), Default::default(), None
+		));
+	}: _(RawOrigin::Signed(nodes[0].clone()), 1)
+	verify {
+		assert_eq!(AssetTracking::<T>::uid_to_shipment(1).map(|shipment| shipment.status), Some(ShipmentStatus::Cancelled));
+		let tail = AssetTracking::<T>::archival_tail();
+		assert_eq!(AssetTracking::<T>::archival_queue(tail - 1).map(|(uid, _)| uid), Some(1));
+	}
+
+	amend_destination {
+		let nodes = create_shipment_over::<T>(3);
+	}: _(RawOrigin::Signed(nodes[0].clone()), 1, nodes[1].clone())
+	verify {
+		assert_last_event::<T>(Event::DestinationAmended(1, nodes[2].clone(), nodes[1].clone()).into());
+	}
+
+	open_dispute {
+		let nodes = create_shipment_over::<T>(2);
+	}: _(RawOrigin::Signed(nodes[0].clone()), 1)
+	verify {
+		assert!(AssetTracking::<T>::disputes(1).is_some());
+	}
+
+	resolve_dispute {
+		let nodes = create_shipment_over::<T>(2);
+		assert_ok!(AssetTracking::<T>::open_dispute(RawOrigin::Signed(nodes[0].clone()).into(), 1));
+		let origin = T::ArbiterOrigin::successful_origin();
+	}: _<T::Origin>(origin, 1, DisputeOutcome::Creator)
+	verify {
+		assert_last_event::<T>(Event::DisputeResolved(1, DisputeOutcome::Creator).into());
+	}
+
+	register_operator {
+		let operator: T::AccountId = account("operator", 0, SEED);
+		let origin = T::NetworkAdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, operator.clone())
+	verify {
+		assert!(AssetTracking::<T>::operators(&operator).is_some());
+	}
+
+	deregister_operator {
+		let operator: T::AccountId = account("operator", 0, SEED);
+		assert_ok!(AssetTracking::<T>::register_operator(T::NetworkAdminOrigin::successful_origin(), operator.clone()));
+		let origin = T::NetworkAdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, operator.clone())
+	verify {
+		assert!(AssetTracking::<T>::operators(&operator).is_none());
+	}
+
+	set_node_operator {
+		let nodes = create_line::<T>(1);
+		let operator: T::AccountId = account("operator", 0, SEED);
+		assert_ok!(AssetTracking::<T>::register_operator(T::NetworkAdminOrigin::successful_origin(), operator.clone()));
+		let origin = T::NetworkAdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, nodes[0].clone(), Some(operator.clone()))
+	verify {
+		assert_eq!(AssetTracking::<T>::node_operator(&nodes[0]), Some(operator));
+	}
+
+	set_capacity {
+		let nodes = create_line::<T>(1);
+		let origin = T::NetworkAdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, nodes[0].clone(), Some(10))
+	verify {
+		assert_eq!(AssetTracking::<T>::capacity(&nodes[0]), Some(10));
+	}
+
+	pack_shipments {
+		let n in 1 .. T::MaxChildren::get();
+		let nodes = create_line::<T>(3);
+		let creator = nodes[0].clone();
+		T::Currency::make_free_balance_be(&creator, BalanceOf::<T>::max_value() / 2u32.into());
+		for _ in 0 ..= n {
+			assert_ok!(AssetTracking::<T>::create_shipment(
+				RawOrigin::Signed(creator.clone()).into(),
+				nodes[2].clone(),
+				T::Hash::default(),
+				Default::default(),
+				None,
+				None,
+				RouteObjective::Cheapest
+			));
+		}
+		let children: BoundedVec<u64, T::MaxChildren> = (2 ..= n as u64 + 1).collect::<Vec<_>>().try_into().unwrap();
+	}: _(RawOrigin::Signed(creator), 1, children)
+	verify {
+		assert_eq!(AssetTracking::<T>::children(1).len(), n as usize);
+	}
+
+	// Unpacking at an intermediate node re-plans the child's route from there
+	unpack_shipment {
+		let nodes = create_line::<T>(T::MaxSize::get());
+		let creator = nodes[0].clone();
+		let destination = nodes[nodes.len() - 1].clone();
+		T::Currency::make_free_balance_be(&creator, BalanceOf::<T>::max_value() / 2u32.into());
+		for uid in 1 ..= 2 {
+			assert_ok!(AssetTracking::<T>::create_shipment(
+				RawOrigin::Signed(creator.clone()).into(),
+				destination.clone(),
+				AssetTracking::<T>::handoff_commitment(uid, 1, &[1u8; 32]),
+				Default::default(),
+				None,
+				None,
+				RouteObjective::Cheapest
+			));
+		}
+		let children: BoundedVec<u64, T::MaxChildren> = vec![2].try_into().unwrap();
+		assert_ok!(AssetTracking::<T>::pack_shipments(RawOrigin::Signed(creator).into(), 1, children));
+		let next_commitment = AssetTracking::<T>::handoff_commitment(1, 2, &[2u8; 32]);
+		assert_ok!(AssetTracking::<T>::update_shipment(
+			RawOrigin::Signed(nodes[1].clone()).into(), 1, [1u8; 32], Some(next_commitment), Default::default(), None
+		));
+		let commitment = AssetTracking::<T>::handoff_commitment(2, 1, &[3u8; 32]);
+	}: _(RawOrigin::Signed(nodes[1].clone()), 2, Some(commitment), Default::default())
+	verify {
+		assert_eq!(AssetTracking::<T>::parent_of(2), None);
+	}
+
+	// A reading breaching the condition limits, which slashes the holder
+	submit_telemetry {
+		let key = <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::RuntimeAppPublic::generate_pair(None);
+		let public: T::Public = <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::GenericPublic::from(key).into();
+		hand_over_breachable::<T>(&public.clone().into_account());
+		let payload = TelemetryPayload::<T> {
+			public: public.clone(),
+			block_number: frame_system::Pallet::<T>::block_number(),
+			shipment_uid: 1,
+			reading: Default::default(),
+		};
+		let signature = <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::sign(&payload.encode(), public).unwrap();
+	}: _(RawOrigin::None, payload, signature)
+	verify {
+		assert!(!AssetTracking::<T>::breaches(1).is_empty());
+	}
+
+	// Reported by the sensor rather than the holder, breaching the condition limits
+	submit_reading {
+		let holder: T::AccountId = account("holder", 0, SEED);
+		hand_over_breachable::<T>(&holder);
+		let sensor: T::AccountId = account("sensor", 0, SEED);
+	}: _(RawOrigin::Signed(sensor), 1, Default::default())
+	verify {
+		assert!(!AssetTracking::<T>::breaches(1).is_empty());
+	}
+
+	withdraw_bond {
+		let nodes = create_line::<T>(1);
+		assert_ok!(AssetTracking::<T>::remove_transit_node(T::NetworkAdminOrigin::successful_origin(), nodes[0].clone()));
+		frame_system::Pallet::<T>::set_block_number(frame_system::Pallet::<T>::block_number() + T::UnbondingPeriod::get());
+	}: _(RawOrigin::Signed(nodes[0].clone()), nodes[0].clone())
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert!(AssetTracking::<T>::bonds(&nodes[0]).is_none());
 	}
 
-	impl_benchmark_test_suite!(Template, crate::mock::new_test_ext(), crate::mock::Test);
+	impl_benchmark_test_suite!(AssetTracking, crate::mock::new_test_ext(), crate::mock::Test);
 }
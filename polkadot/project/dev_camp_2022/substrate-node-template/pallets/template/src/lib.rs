@@ -1,474 +1,3148 @@
-#![cfg_attr(not(feature = "std"), no_std)]
-
-  pub use pallet::*;
-
-  #[cfg(test)]
-  mod mock;
-
-  #[cfg(test)]
-  mod tests;
-
-  #[frame_support::pallet]
-  pub mod pallet {
-
-	use frame_support::{
-		pallet_prelude::*,
-		traits::{Currency, Randomness},
-		BoundedVec,
-	};
-	use frame_system::pallet_prelude::*;
-	use scale_info::{
-		TypeInfo,
-	};
-	use sp_io::hashing::blake2_128;
-	use sp_runtime::ArithmeticError;
-	use sp_std::{vec,vec::Vec};
-
-
-	#[cfg(feature = "std")]
-	use frame_support::serde::{Deserialize, Serialize};
-
-	type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
-
-	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
-	#[scale_info(skip_type_params(T))]
-	pub struct Shipment<T: Config> {
-		pub creator: T::AccountId,
-		pub fees: Option<BalanceOf<T>>,
-		pub owner_index: u8,
-		pub route: BoundedVec<T::AccountId,T::MaxSize>,
-		pub destination: T::AccountId,
-		pub uid: u64,
-		pub status: ShipmentStatus,
-	}
-
-	#[derive(Clone, Encode, Decode, PartialEq, Copy, RuntimeDebug, TypeInfo, MaxEncodedLen)]
-	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-	pub enum ShipmentStatus {
-		InTransit,
-		Delivered,
-		Failed,
-	}
-
-	// The struct on which we build all of our Pallet logic.
-	#[pallet::pallet]
-	#[pallet::generate_store(pub(super) trait Store)]
-	#[pallet::without_storage_info]
-	pub struct Pallet<T>(_);
-
-    /* Placeholder for defining custom types. */
-
-	#[pallet::config]
-	pub trait Config: frame_system::Config {
-		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
-		type Currency: Currency<Self::AccountId>;
-		type KeyRandomNess: Randomness<Self::Hash, Self::BlockNumber>;
-		type MaxSize: Get<u32>;
-	}
-
-	#[pallet::event]
-	#[pallet::generate_deposit(pub(super) fn deposit_event)]
-	pub enum Event<T: Config> {
-		TransitPointCreated(T::AccountId),
-		TransitPointRemoved(T::AccountId),
-		NeighbourUpdated(T::AccountId,T::AccountId),
-		ShipmentCreated(T::AccountId),
-		ShipmentUpdated(T::AccountId),
-		ShipmentReceived(T::AccountId),
-	}
-
-	#[pallet::error]
-	pub enum Error<T> {
-		InvalidUID,
-		InvalidShipmentUID,
-		InvalidRoute,
-		InvalidKey,
-		KeyNotFound,
-		ShipmentAlreadyExists,
-		ShipmentKeyAlreadyExists,
-		ShipmentNotFound,
-		TransitPointAlreadyExists,
-		TransitNodesOverFlow,
-		TransitPointNotFound,
-		UIDNotFound,
-		UnauthorizedCaller,
-		CallerIsNotFirstNode
-	}
-
-	#[pallet::storage]
-	#[pallet::getter(fn count_for_transit_point)]
-	pub(super) type CountForTransitPoints<T:Config> = StorageValue<
-		_,
-		u64,
-		ValueQuery,
-	>;
-
-	#[pallet::storage]
-	#[pallet::getter(fn route_costs)]
-	pub(super) type RouteCosts<T:Config> = StorageDoubleMap<
-		_,
-		Blake2_128Concat,
-		T::AccountId,
-		Blake2_128Concat,
-		T::AccountId,
-		u32,
-		OptionQuery,
-	>;
-
-	#[pallet::storage]
-	#[pallet::getter(fn route_vec)]
-	pub(super) type RouteVector<T:Config> = StorageDoubleMap<
-		_,
-		Blake2_128Concat,
-		T::AccountId,
-		Blake2_128Concat,
-		T::AccountId,
-		Vec<T::AccountId>,
-		OptionQuery,
-	>;
-
-	// shipment_uid -> key map
-	#[pallet::storage]
-	#[pallet::getter(fn shipment_uid_to_key)]
-	pub(super) type UIDToKey<T:Config> = StorageMap<
-		_,
-		Blake2_128Concat,
-		u64,
-		[u8; 16],
-		OptionQuery,
-	>;
-
-	// shipment_uid -> shipment map
-	#[pallet::storage]
-	#[pallet::getter(fn uid_to_shipment)]
-	pub(super) type UIDToShipment<T:Config> = StorageMap<
-		_,
-		Blake2_128Concat,
-		u64,
-		Shipment<T>,
-		OptionQuery,
-	>;
-
-	#[pallet::storage]
-	#[pallet::getter(fn shipment_uid)]
-	pub(super) type ShipmentUID<T:Config> = StorageValue<
-		_,
-		u64,
-		ValueQuery,
-	>;
-
-	#[pallet::storage]
-	#[pallet::getter(fn transit_nodes)]
-	pub(super) type TransitNodes<T:Config> = StorageValue<
-		_,
-		Vec<T::AccountId>,
-		ValueQuery,
-	>;
-
-	#[pallet::storage]
-	pub(super) type Nonce<T:Config> = StorageValue<
-		_,
-		u32,
-		ValueQuery,
-	>;
-
-
-    #[pallet::call]
-    impl<T: Config> Pallet<T> {
-
-		#[pallet::weight(0)]
-		pub fn create_new_transit_node(
-			origin: OriginFor<T>,
-			transit_node: T::AccountId,
-			neighbours: BoundedVec<(T::AccountId, u32), T::MaxSize>
-		) -> DispatchResult {
-
-			ensure_root(origin)?;
-			ensure!(!Self::transit_nodes().contains(&transit_node), Error::<T>::TransitPointAlreadyExists);
-			ensure!(
-				neighbours.iter().all(|neighbour| neighbour.0 != transit_node && Self::transit_nodes().contains(&neighbour.0)),
-				Error::<T>::InvalidRoute);
-
-			for neighbour in neighbours.iter() {
-				RouteCosts::<T>::insert(transit_node.clone(), neighbour.0.clone(), neighbour.1);
-				RouteCosts::<T>::insert(neighbour.0.clone(), transit_node.clone(), neighbour.1);
-			}
-
-			TransitNodes::<T>::append(transit_node.clone());
-			let transit_point_counts = Self::count_for_transit_point().checked_add(1).ok_or(ArithmeticError::Overflow)?;
-			CountForTransitPoints::<T>::put(transit_point_counts);
-
-			Self::deposit_event(Event::TransitPointCreated(transit_node));
-
-			Ok(())
-		}
-
-		#[pallet::weight(0)]
-		pub fn update_neighbour(
-			origin: OriginFor<T>,
-			node1: T::AccountId,
-			node2: T::AccountId,
-			cost: u32
-		) ->DispatchResult {
-			ensure_root(origin)?;
-			ensure!(Self::transit_nodes().contains(&node1) && Self::transit_nodes().contains(&node2), Error::<T>::TransitPointNotFound);
-
-			RouteCosts::<T>::insert(node1.clone(),node2.clone(),cost.clone());
-			RouteCosts::<T>::insert(node2.clone(),node1.clone(),cost.clone());
-
-			Self::deposit_event(Event::NeighbourUpdated(node1,node2));
-
-			Ok(())
-		}
-
-		#[pallet::weight(0)]
-		pub fn remove_transit_node(origin: OriginFor<T>, transit_node: T::AccountId) -> DispatchResult {
-
-			ensure_root(origin)?;
-			ensure!(Self::transit_nodes().contains(&transit_node), Error::<T>::TransitPointNotFound);
-
-			RouteCosts::<T>::remove_prefix(&transit_node, None);
-			for node in Self::transit_nodes() {
-				if node == transit_node {
-					continue;
-				}
-				if RouteCosts::<T>::contains_key(&node, &transit_node) {
-					RouteCosts::<T>::remove(&node, &transit_node);
-				}
-			}
-
-			let transit_point_counts = Self::count_for_transit_point().checked_sub(1).ok_or(ArithmeticError::Underflow)?;
-			let mut new_transit_nodes = Self::transit_nodes();
-			new_transit_nodes.retain(|nodes| *nodes == transit_node);
-
-			CountForTransitPoints::<T>::put(transit_point_counts);
-			TransitNodes::<T>::put(new_transit_nodes);
-
-			Self::deposit_event(Event::TransitPointRemoved(transit_node));
-
-			Ok(())
-		}
-
-		#[pallet::weight(0)]
-		pub fn create_shipment(origin: OriginFor<T>, destination: T::AccountId) -> DispatchResult {
-
-			let transit_node = ensure_signed(origin)?;
-
-			let shipment_uid = Self::shipment_uid().checked_add(1).ok_or(ArithmeticError::Overflow)?;
-			
-			Self::get_random_route(transit_node.clone(), destination.clone());
-
-			//let route1 = Self::route_vec(transit_node.clone(),destination.clone()).unwrap();
-
-			let shipment = Shipment::<T> {
-				creator: transit_node.clone(),
-				fees: None, // Todo: Calculate fees based on the route
-				owner_index: 1,
-				route: Self::get_random_route(transit_node.clone(),destination.clone()),
-				destination: destination.clone(),
-				uid: shipment_uid.clone(),
-				status: ShipmentStatus::InTransit
-			};
-
-			ensure!(!UIDToShipment::<T>::contains_key(&shipment_uid), Error::<T>::ShipmentAlreadyExists);
-			UIDToShipment::<T>::insert(&shipment_uid, &shipment);
-
-			let key = Self::gen_key();
-			UIDToKey::<T>::insert(&shipment_uid, &key);
-			ShipmentUID::<T>::put(shipment_uid);
-
-			Self::deposit_event(Event::ShipmentCreated(transit_node));
-
-			Ok(())
-		}
-
-		#[pallet::weight(0)]
-		pub fn update_shipment(origin: OriginFor<T>, shipment_uid: u64, key: [u8; 16]) -> DispatchResult {
-
-			let transit_node = ensure_signed(origin)?;
-			let mut shipment = Self::uid_to_shipment(shipment_uid).ok_or(Error::<T>::ShipmentNotFound)?;
-
-			ensure!(UIDToKey::<T>::contains_key(&shipment_uid), Error::<T>::UIDNotFound);
-			ensure!(Self::shipment_uid_to_key(&shipment_uid).unwrap() == key, Error::<T>::InvalidKey);
-			ensure!(UIDToShipment::<T>::contains_key(&shipment_uid), Error::<T>::ShipmentNotFound);
-			ensure!(&transit_node == shipment.route.get(shipment.owner_index as usize).unwrap(), Error::<T>::UnauthorizedCaller);
-
-			UIDToKey::<T>::remove(&shipment_uid);
-
-			match transit_node == shipment.destination {
-				true => {
-					// Shipment has reached end destination
-					shipment.owner_index = 0;
-					shipment.status = ShipmentStatus::Delivered;
-					UIDToShipment::<T>::insert(&shipment_uid, &shipment);
-					Self::deposit_event(Event::ShipmentReceived(transit_node));
-				},
-				false => {
-					// Shipment is still in transit
-					shipment.owner_index = shipment.owner_index + 1;
-					let new_key = Self::gen_key();
-					UIDToKey::<T>::insert(&shipment_uid, &new_key);
-					UIDToShipment::<T>::insert(&shipment_uid, &shipment);
-					Self::deposit_event(Event::ShipmentUpdated(transit_node));
-				}
-			}
-
-			Ok(())
-		}
-	}
-
-	// Helpful functions
-	impl<T: Config> Pallet<T> {
-
-		fn gen_key() -> [u8; 16] {
-			let payload = (
-				T::KeyRandomNess::random(&b"key"[..]).0,
-				<frame_system::Pallet<T>>::extrinsic_index().unwrap_or_default(),
-				<frame_system::Pallet<T>>::block_number(),
-			);
-			payload.using_encoded(blake2_128)
-		}
-
-		fn get_and_increment_nonce() -> Vec<u8> {
-			let nonce = Nonce::<T>::get();
-			Nonce::<T>::put(nonce.wrapping_add(1));
-			nonce.encode()
-		}
-
-		fn get_random_route(origin: T::AccountId, dest: T::AccountId) -> BoundedVec<T::AccountId,T::MaxSize> {
-
-			let count: u64 = Self::count_for_transit_point();
-			let mut route: BoundedVec<_, _>;
-			let nodes = TransitNodes::<T>::get();
-
-			if count < 3 {
-				//let route_vec1: BoundedVec<_, _> = bounded_vec![origin.clone(),dest.clone()];
-				//let route_vec2: BoundedVec<_, _> = bounded_vec![dest.clone(),origin.clone()];
-
-				route = vec![origin.clone(),dest.clone()].try_into().unwrap();
-
-				//RouteVector::<T>::insert(origin.clone(),dest.clone(),vec![origin.clone(),dest.clone()]);
-				//RouteVector::<T>::insert(dest.clone(),origin.clone(),vec![dest.clone(),origin.clone()]);
-			} else if count >= 3 && count <= 5 {
-				let nonce = Self::get_and_increment_nonce();
-				let rv1 = T::KeyRandomNess::random(&nonce).encode();
-				let mut rn1 = u64::decode(&mut rv1.as_ref()).unwrap();
-				let div2: u64 = 2;
-
-				let rv2 = rn1 % div2;
-
-				match rv2 {
-					0 => {
-						route = vec![origin.clone(),dest.clone()].try_into().unwrap();
-					},
-					1 => {
-						let nonce2 = Self::get_and_increment_nonce();      
-						let mut rv3 = T::KeyRandomNess::random(&nonce2).encode();
-						rn1 = u64::decode(&mut rv3.as_ref()).unwrap();
-						//let count2 = count - 2;
-
-						let mut rv4 = rn1 % count;
-
-						while nodes[rv4 as usize] == origin || nodes[rv4 as usize] == dest {
-							let nonce3 = Self::get_and_increment_nonce();    
-							rv3 = T::KeyRandomNess::random(&nonce3).encode();  
-							rn1 = u64::decode(&mut rv3.as_ref()).unwrap();
-							rv4 = rn1 % count;
-						}
-
-						route = vec![origin.clone(),nodes[rv4 as usize].clone(),dest.clone()].try_into().unwrap();
- 					},
-					 _ => {
-						 route = vec![].try_into().unwrap();
-					 }
-				}
-			} else  {
-				let nonce = Self::get_and_increment_nonce();
-				let rv1 = T::KeyRandomNess::random(&nonce).encode();
-				let mut rn1 = u64::decode(&mut rv1.as_ref()).unwrap();
-				let div3: u64 = 3;
-
-				let rv2 = rn1 % div3;
-				match rv2 {
-					0 => {
-						route = vec![origin.clone(),dest.clone()].try_into().unwrap();
-					},
-					1 => {
-						let nonce2 = Self::get_and_increment_nonce();      
-						let mut rv3 = T::KeyRandomNess::random(&nonce2).encode();
-						rn1 = u64::decode(&mut rv3.as_ref()).unwrap();
-
-						let mut rv4 = rn1 % count;
-
-						while nodes[rv4 as usize] == origin || nodes[rv4 as usize] == dest {
-							let nonce3 = Self::get_and_increment_nonce();    
-							rv3 = T::KeyRandomNess::random(&nonce3).encode();  
-							rn1 = u64::decode(&mut rv3.as_ref()).unwrap();
-							rv4 = rn1 % count;
-						}
-
-						route = vec![origin.clone(),nodes[rv4 as usize].clone(),dest.clone()].try_into().unwrap();
-
-					},
-					2 => {
-						let nonce2 = Self::get_and_increment_nonce();
-						let mut rv3 = T::KeyRandomNess::random(&nonce2).encode();
-						rn1 = u64::decode(&mut rv3.as_ref()).unwrap();
-						
-
-						let mut rv4 = rn1 % count;
-
-						while nodes[rv4 as usize] == origin || nodes[rv4 as usize] == dest {
-							let nonce3 = Self::get_and_increment_nonce();    
-							rv3 = T::KeyRandomNess::random(&nonce3).encode();
-							rn1 = u64::decode(&mut rv3.as_ref()).unwrap();  
-							rv4 = rn1 % count;
-						}
-
-						let nonce5 = Self::get_and_increment_nonce();    
-						rv3 = T::KeyRandomNess::random(&nonce5).encode();
-						rn1 = u64::decode(&mut rv3.as_ref()).unwrap();  
-						let mut rv5 = rn1 % count;
-
-						while nodes[rv5 as usize] == origin || nodes[rv5 as usize] == dest || rv5 == rv4 {
-							let nonce6 = Self::get_and_increment_nonce();    
-							rv3 = T::KeyRandomNess::random(&nonce6).encode();
-							rn1 = u64::decode(&mut rv3.as_ref()).unwrap();  
-							rv5 = rn1 % count;
-						}
-
-						route = vec![origin.clone(),nodes[rv4 as usize].clone(),nodes[rv5 as usize].clone(),dest.clone()].try_into().unwrap();
-
-					},
-					_ => {
-						route = vec![].try_into().unwrap();
-					}
-				}
-			}
-
-			route
-		}
-
-		// fn set_fees() {}
-
-		// fn route() {}
-
-		// fn get_transit_nodes() {}
-
-		// fn get_transit_status() {}
-
-		// I think we should be looking to develop an encode decode algorithm. Public key is encoded but only the decoded private key
-		// can be used to call the update function.
-
-		// fn get_key(origin: OriginFor<T>, shipment_uid: u64) -> Result<[u8; 16], Error<T>> {
-		// 	let transit_node = match ensure_signed(origin) {
-		// 		Ok(val) => val,
-		// 		Err(_) => return Err(Error::<T>::UnauthorizedCaller)
-		// 	};
-		// 	ensure!(Self::transit_nodes().contains(&transit_node), Error::<T>::UnauthorizedCaller);
-		// 	ensure!(ShipmentUidToKey::<T>::contains_key(&shipment_uid), Error::<T>::UIDNotFound);
-
-		// 	return ShipmentUidToKey::<T>::get(&shipment_uid).ok_or(Error::<T>::KeyNotFound);
-		// }
-	}
-  }
+#![cfg_attr(not(feature = "std"), no_std)]
+
+  pub use pallet::*;
+
+  #[cfg(test)]
+  mod mock;
+
+  #[cfg(test)]
+  mod tests;
+
+  #[cfg(feature = "runtime-benchmarks")]
+  mod benchmarking;
+
+  pub mod migrations;
+  pub mod weights;
+  pub use weights::WeightInfo;
+
+  use sp_core::crypto::KeyTypeId;
+
+  /// Key type of the keys transit nodes sign telemetry with.
+  pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"asst");
+
+  /// Offchain local storage key of the telemetry endpoint polled by the offchain worker,
+  /// set as raw UTF-8 with the `offchain_localStorageSet` RPC (`PERSISTENT` kind).
+  pub const TELEMETRY_ENDPOINT_KEY: &[u8] = b"asset-tracking::telemetry-endpoint";
+
+  /// Most shipment uids a single `shipments_by` page returns, whatever limit is asked for.
+  pub const MAX_SHIPMENTS_PAGE: u32 = 100;
+
+  /// Most blocks a single `emissions_report` covers, whatever range is asked for.
+  pub const MAX_REPORT_BLOCKS: u32 = 10_000;
+
+  pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::Signature as Sr25519Signature;
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		traits::Verify,
+		MultiSignature, MultiSigner,
+	};
+	app_crypto!(sr25519, KEY_TYPE);
+
+	pub struct TelemetryAuthId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for TelemetryAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+
+	impl frame_system::offchain::AppCrypto<<Sr25519Signature as Verify>::Signer, Sr25519Signature>
+		for TelemetryAuthId
+	{
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+  }
+
+  #[frame_support::pallet]
+  pub mod pallet {
+
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{BalanceStatus, Currency, ReservableCurrency},
+		transactional,
+		BoundedVec,
+	};
+	use frame_system::{
+		offchain::{AppCrypto, SendTransactionTypes, SendUnsignedTransaction, SignedPayload, Signer, SigningTypes},
+		pallet_prelude::*,
+	};
+	use crate::{weights::WeightInfo, TELEMETRY_ENDPOINT_KEY};
+	use lite_json::json::JsonValue;
+	use scale_info::{
+		TypeInfo,
+	};
+	use sp_runtime::{
+		offchain::{
+			http,
+			storage::{MutateStorageError, StorageRetrievalError, StorageValueRef},
+			Duration, StorageKind,
+		},
+		traits::{Hash as HashT, IdentifyAccount, One, Saturating, UniqueSaturatedInto, Zero},
+		ArithmeticError, RuntimeAppPublic,
+	};
+	use sp_std::{
+		collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+		vec,
+		vec::Vec,
+	};
+
+
+	#[cfg(feature = "std")]
+	use frame_support::serde::{Deserialize, Serialize};
+
+	pub type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Shipment<T: Config> {
+		pub creator: T::AccountId,
+		pub fees: Option<BalanceOf<T>>,
+		/// Fee of each leg, `leg_fees[i]` covering `route[i]` -> `route[i + 1]`.
+		pub leg_fees: BoundedVec<BalanceOf<T>, T::MaxSize>,
+		/// Block by which each leg must be completed, indexed like `leg_fees`.
+		pub leg_deadlines: BoundedVec<T::BlockNumber, T::MaxSize>,
+		pub owner_index: u8,
+		pub route: BoundedVec<T::AccountId,T::MaxSize>,
+		pub destination: T::AccountId,
+		pub uid: u64,
+		pub status: ShipmentStatus,
+		/// What the route is optimised for, also when rerouting.
+		pub objective: RouteObjective,
+		/// Edge attributes summed along the route as last planned.
+		pub metrics: RouteMetrics<T>,
+	}
+
+	/// Handling requirements of a shipment, as a bitmask of the associated constants.
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, Copy, Default, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct HandlingFlags(pub u8);
+
+	impl HandlingFlags {
+		pub const FRAGILE: u8 = 1 << 0;
+		pub const HAZARDOUS: u8 = 1 << 1;
+		pub const PERISHABLE: u8 = 1 << 2;
+		pub const KEEP_UPRIGHT: u8 = 1 << 3;
+		pub const ALL: u8 = Self::FRAGILE | Self::HAZARDOUS | Self::PERISHABLE | Self::KEEP_UPRIGHT;
+
+		pub fn contains(&self, flag: u8) -> bool {
+			self.0 & flag == flag
+		}
+	}
+
+	/// What is being shipped, bound to the on-chain shipment at creation.
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct ShipmentMetadata<T: Config> {
+		/// Hash or IPFS CID of the goods description.
+		pub content: BoundedVec<u8, T::MaxContentIdLen>,
+		/// Mass in grams.
+		pub mass: u32,
+		/// Volume in cubic centimetres.
+		pub volume: u32,
+		pub declared_value: BalanceOf<T>,
+		pub flags: HandlingFlags,
+	}
+
+	/// Custody taken by `holder`, the node at `route[hop]` of a shipment.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct CustodyRecord<T: Config> {
+		pub hop: u8,
+		pub holder: T::AccountId,
+		pub block: T::BlockNumber,
+		pub moment: T::Moment,
+		/// Hash of an off-chain note about the handoff, e.g. a delivery receipt.
+		pub note: Option<T::Hash>,
+	}
+
+	/// Records kept apart from a shipment that its `archive_hash` commits to.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct ShipmentRecords<T: Config> {
+		pub metadata: Option<ShipmentMetadata<T>>,
+		pub conditions: Option<ConditionLimits<T>>,
+		pub breaches: BoundedVec<u8, T::MaxSize>,
+		pub rejection: Option<(RejectionReason, u64)>,
+		pub planned_arrivals: BoundedVec<T::BlockNumber, T::MaxSize>,
+		pub latest_telemetry: Option<TelemetryReading>,
+		pub leg_emissions: BoundedVec<(u8, u64, T::BlockNumber), T::MaxSize>,
+		pub emissions: u64,
+		/// Shipment this one returns to its creator, if any.
+		pub return_of: Option<u64>,
+	}
+
+	/// Sensor reading of the IoT device travelling with a shipment.
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, Default, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct TelemetryReading {
+		/// Latitude in microdegrees.
+		pub latitude: i32,
+		/// Longitude in microdegrees.
+		pub longitude: i32,
+		/// Temperature in hundredths of a degree Celsius.
+		pub temperature: i16,
+		/// Relative humidity in hundredths of a percent, if the device measures it.
+		pub humidity: Option<u16>,
+		/// Milliseconds since the Unix epoch, as reported by the device.
+		pub timestamp: u64,
+	}
+
+	/// Conditions a shipment must be kept in, declared by its creator.
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct ConditionLimits<T: Config> {
+		/// Temperature bounds in hundredths of a degree Celsius.
+		pub min_temperature: i16,
+		pub max_temperature: i16,
+		/// Relative humidity bounds in hundredths of a percent.
+		pub min_humidity: u16,
+		pub max_humidity: u16,
+		/// Taken from the bond of the holder for each hop during which a limit is breached.
+		pub penalty: BalanceOf<T>,
+		/// Device travelling with the shipment that may report readings besides the holder.
+		pub sensor: Option<T::AccountId>,
+	}
+
+	impl<T: Config> ConditionLimits<T> {
+		pub fn is_breached_by(&self, reading: &TelemetryReading) -> bool {
+			reading.temperature < self.min_temperature || reading.temperature > self.max_temperature ||
+				reading.humidity.map_or(false, |humidity| humidity < self.min_humidity || humidity > self.max_humidity)
+		}
+	}
+
+	/// A reading signed off-chain with the telemetry key of the shipment's holder.
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct TelemetryPayload<T: Config> {
+		pub public: T::Public,
+		pub block_number: T::BlockNumber,
+		pub shipment_uid: u64,
+		pub reading: TelemetryReading,
+	}
+
+	impl<T: Config> SignedPayload<T> for TelemetryPayload<T> {
+		fn public(&self) -> T::Public {
+			self.public.clone()
+		}
+	}
+
+	impl<T: Config> Shipment<T> {
+		/// Whether the shipment has reached its destination, accepted or not.
+		pub fn has_arrived(&self) -> bool {
+			self.status == ShipmentStatus::Delivered || self.status == ShipmentStatus::AwaitingAcceptance
+		}
+
+		/// Transit node that last took custody, while the shipment is in transit.
+		pub fn current_holder(&self) -> Option<&T::AccountId> {
+			match self.status {
+				ShipmentStatus::InTransit => self.route.get((self.owner_index as usize).saturating_sub(1)),
+				_ => None,
+			}
+		}
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, Copy, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum ShipmentStatus {
+		InTransit,
+		Delivered,
+		Failed,
+		/// Reached its destination, which has yet to accept or reject it.
+		AwaitingAcceptance,
+		/// Refused by its destination and sent back to its creator as a return shipment.
+		Rejected,
+		/// Withdrawn by its creator while in transit.
+		Cancelled,
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, Copy, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum RejectionReason {
+		Damaged,
+		Incomplete,
+		WrongContent,
+		ConditionsBreached,
+		NotOrdered,
+		Other,
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, Copy, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum DisputeOutcome {
+		/// The shipment is failed and the unspent fees are refunded to the creator.
+		Creator,
+		/// The current holder keeps custody and the shipment continues its route.
+		Holder,
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, Copy, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum TransportMode {
+		Road,
+		Rail,
+		Sea,
+		Air,
+		Other,
+	}
+
+	/// Physical description of an edge, from which its emissions are estimated.
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, Copy, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct EdgeProfile {
+		/// Length of the edge in kilometres.
+		pub distance: u32,
+		/// Emits `distance` times the `EmissionFactors` entry of the mode.
+		pub mode: TransportMode,
+	}
+
+	/// Everything known about the edge from one transit node to another.
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct EdgeAttributes<T: Config> {
+		/// Charged at `PricePerCostUnit` for each leg over the edge.
+		pub cost: u32,
+		/// Expected transit time in blocks. Edges without one are expected to take `LegTimeout`.
+		pub transit_time: Option<T::BlockNumber>,
+		pub profile: Option<EdgeProfile>,
+	}
+
+	impl<T: Config> EdgeAttributes<T> {
+		pub fn with_cost(cost: u32) -> Self {
+			Self { cost, transit_time: None, profile: None }
+		}
+	}
+
+	/// What the route of a shipment is optimised for.
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, Copy, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum RouteObjective {
+		/// Lowest summed edge cost, and so the lowest fees.
+		Cheapest,
+		/// Shortest summed transit time.
+		Fastest,
+		/// Lowest summed emissions. Edges without a profile, or whose mode has no emission
+		/// factor, count as emission-free.
+		Greenest,
+		/// Lowest sum of cost, transit time in blocks and emissions in grams, each multiplied
+		/// by its weight.
+		Weighted { cost: u32, transit_time: u32, emissions: u32 },
+	}
+
+	/// Edge attributes summed along a route.
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct RouteMetrics<T: Config> {
+		pub cost: u64,
+		pub transit_time: T::BlockNumber,
+		/// Grams of CO₂ equivalent.
+		pub emissions: u64,
+	}
+
+	/// Part of the transit network that changed, whose in-transit shipments are rerouted.
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum NetworkChange<AccountId> {
+		/// A transit node was removed.
+		Node(AccountId),
+		/// The edge from the first node to the second changed.
+		Edge(AccountId, AccountId),
+		/// The edge between two nodes changed in both directions.
+		Lane(AccountId, AccountId),
+	}
+
+	impl<AccountId: PartialEq> NetworkChange<AccountId> {
+		/// Node whose `ShipmentsByRouteNode` entries include every shipment the change affects.
+		pub fn node(&self) -> &AccountId {
+			match self {
+				NetworkChange::Node(node) | NetworkChange::Edge(node, _) | NetworkChange::Lane(node, _) => node,
+			}
+		}
+
+		/// Whether the route of a shipment from its current holder on is affected.
+		pub fn affects(&self, remaining: &[AccountId]) -> bool {
+			match self {
+				NetworkChange::Node(node) => remaining.contains(node),
+				NetworkChange::Edge(from, to) => remaining.windows(2).any(|leg| leg[0] == *from && leg[1] == *to),
+				NetworkChange::Lane(node1, node2) => remaining.windows(2).any(|leg|
+					(leg[0] == *node1 && leg[1] == *node2) || (leg[0] == *node2 && leg[1] == *node1)),
+			}
+		}
+	}
+
+	/// Relation of an account to the shipments listed by `shipments_by`.
+	#[derive(Clone, Encode, Decode, PartialEq, Eq, Copy, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum ShipmentRole {
+		Creator,
+		Destination,
+		/// Directly holds the in-transit shipment. Packed shipments are listed under their parent.
+		Holder,
+	}
+
+	/// The current storage version.
+	pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+	/// Database reads and writes of failing a single shipment, not counting those packed into it.
+	const FAIL_SHIPMENT_READS_WRITES: u64 = 8;
+
+	// The struct on which we build all of our Pallet logic.
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
+	pub struct Pallet<T>(_);
+
+    /* Placeholder for defining custom types. */
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_timestamp::Config + SendTransactionTypes<Call<Self>> + SigningTypes {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		type Currency: ReservableCurrency<Self::AccountId>;
+		type MaxSize: Get<u32>;
+		/// Upper bound on the number of labels settled while searching for a route.
+		#[pallet::constant]
+		type MaxRouteSearchIterations: Get<u32>;
+		/// Upper bound on the outgoing edges of a transit node, and so on the edges a route
+		/// search reads per settled label.
+		#[pallet::constant]
+		type MaxEdgesPerNode: Get<u32>;
+		/// Price charged per unit of edge cost along a shipment's route.
+		#[pallet::constant]
+		type PricePerCostUnit: Get<BalanceOf<Self>>;
+		/// Number of blocks allowed for each leg of a route.
+		#[pallet::constant]
+		type LegTimeout: Get<Self::BlockNumber>;
+		/// Maximum number of leg deadlines expired in a block. Deadlines beyond it are expired
+		/// in the following blocks.
+		#[pallet::constant]
+		type MaxDeadlinesPerBlock: Get<u32>;
+		/// Maximum number of in-transit shipments rerouted after a change to the transit network,
+		/// and in each following block. The remaining shipments wait in `RerouteQueue`.
+		#[pallet::constant]
+		type MaxReroutesPerBlock: Get<u32>;
+		/// Origin allowed to resolve shipment disputes.
+		type ArbiterOrigin: EnsureOrigin<Self::Origin>;
+		/// Origin allowed to manage every transit node and the operator registry.
+		type NetworkAdminOrigin: EnsureOrigin<Self::Origin>;
+		/// Amount reserved when a transit node joins the network, from the operator creating it
+		/// or from the node itself when created by the network admin.
+		#[pallet::constant]
+		type TransitNodeBond: Get<BalanceOf<Self>>;
+		/// Amount of the holder's bond paid to the creator when a shipment fails in its custody.
+		#[pallet::constant]
+		type FailureSlash: Get<BalanceOf<Self>>;
+		/// Amount of the remaining fees paid to the holder when the creator cancels a shipment
+		/// after its first handoff.
+		#[pallet::constant]
+		type CancellationPenalty: Get<BalanceOf<Self>>;
+		/// Number of blocks a removed transit node waits before withdrawing its bond.
+		#[pallet::constant]
+		type UnbondingPeriod: Get<Self::BlockNumber>;
+		/// Number of blocks a delivered, failed, rejected or cancelled shipment stays in storage before
+		/// `on_idle` archives it.
+		#[pallet::constant]
+		type RetentionPeriod: Get<Self::BlockNumber>;
+		/// Maximum length of the content hash or CID in shipment metadata.
+		#[pallet::constant]
+		type MaxContentIdLen: Get<u32>;
+		/// Maximum mass of a shipment, in grams.
+		#[pallet::constant]
+		type MaxMass: Get<u32>;
+		/// Maximum volume of a shipment, in cubic centimetres.
+		#[pallet::constant]
+		type MaxVolume: Get<u32>;
+		/// Maximum declared value of a shipment.
+		#[pallet::constant]
+		type MaxDeclaredValue: Get<BalanceOf<Self>>;
+		/// Maximum number of shipments packed into one parent shipment.
+		#[pallet::constant]
+		type MaxChildren: Get<u32>;
+		/// Key the offchain worker signs telemetry with.
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+		/// Minimum number of blocks between two readings of the same shipment, which is
+		/// also how often the offchain worker polls the telemetry endpoint.
+		#[pallet::constant]
+		type TelemetryInterval: Get<Self::BlockNumber>;
+		/// Priority of unsigned telemetry transactions.
+		#[pallet::constant]
+		type TelemetryPriority: Get<TransactionPriority>;
+		/// Maximum length of a handoff secret sealed to the next holder.
+		#[pallet::constant]
+		type MaxSealedSecretLen: Get<u32>;
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		TransitPointCreated(T::AccountId),
+		TransitPointRemoved(T::AccountId),
+		NeighbourUpdated(T::AccountId,T::AccountId),
+		/// The cost of one direction of an edge changed, or that direction was removed. [from, to, cost]
+		DirectedEdgeSet(T::AccountId, T::AccountId, Option<u32>),
+		ShipmentCreated(T::AccountId),
+		ShipmentUpdated(T::AccountId),
+		ShipmentReceived(T::AccountId),
+		/// Fees have been reserved from the creator. [shipment_uid, creator, fees]
+		ShipmentFeesReserved(u64, T::AccountId, BalanceOf<T>),
+		/// A leg fee has been released to a transit node. [shipment_uid, transit_node, fee]
+		ShipmentFeePaid(u64, T::AccountId, BalanceOf<T>),
+		/// Unspent fees have been returned to the creator. [shipment_uid, creator, refund]
+		ShipmentFeesRefunded(u64, T::AccountId, BalanceOf<T>),
+		/// A leg was not completed before its deadline. [shipment_uid]
+		ShipmentTimedOut(u64),
+		/// The creator disputed a shipment. [shipment_uid, creator]
+		DisputeOpened(u64, T::AccountId),
+		/// A dispute has been resolved. [shipment_uid, outcome]
+		DisputeResolved(u64, DisputeOutcome),
+		/// Handoff secret encrypted to the next holder. [shipment_uid, next_holder, sealed_secret]
+		HandoffSecretSealed(u64, T::AccountId, BoundedVec<u8, T::MaxSealedSecretLen>),
+		/// The remaining legs of a shipment were re-planned. [shipment_uid, old_route, new_route]
+		ShipmentRerouted(u64, BoundedVec<T::AccountId, T::MaxSize>, BoundedVec<T::AccountId, T::MaxSize>),
+		/// An operator may now manage its own transit nodes. [operator]
+		OperatorRegistered(T::AccountId),
+		/// An operator lost its rights over the transit network. [operator]
+		OperatorDeregistered(T::AccountId),
+		/// The operator of a transit node changed. [transit_node, operator]
+		NodeOperatorSet(T::AccountId, Option<T::AccountId>),
+		/// The bond of a transit node was reserved from its bonder. [transit_node, bonder, bond]
+		TransitNodeBonded(T::AccountId, T::AccountId, BalanceOf<T>),
+		/// Bond paid to the creator of a shipment failed in the node's custody. [transit_node, shipment_uid, amount]
+		TransitNodeSlashed(T::AccountId, u64, BalanceOf<T>),
+		/// The bond of a removed transit node went back to its bonder. [transit_node, amount]
+		TransitNodeUnbonded(T::AccountId, BalanceOf<T>),
+		/// A transit node took custody of a shipment. [shipment_uid, hop, holder]
+		CustodyTransferred(u64, u8, T::AccountId),
+		/// Shipments were packed into a parent shipment. [parent_uid, child_uids]
+		ShipmentsPacked(u64, BoundedVec<u64, T::MaxChildren>),
+		/// A shipment was unpacked from its parent. [parent_uid, child_uid, transit_node]
+		ShipmentUnpacked(u64, u64, T::AccountId),
+		/// The holder reported a telemetry reading. [shipment_uid, holder, reading]
+		TelemetryRecorded(u64, T::AccountId, TelemetryReading),
+		/// A reading fell outside the shipment's condition limits. [shipment_uid, holder, reading]
+		ConditionsBreached(u64, T::AccountId, TelemetryReading),
+		/// The expected transit duration of an edge changed. [node1, node2, duration]
+		EdgeDurationSet(T::AccountId, T::AccountId, Option<T::BlockNumber>),
+		/// The distance and transport mode of an edge changed. [node1, node2, profile]
+		EdgeProfileSet(T::AccountId, T::AccountId, Option<EdgeProfile>),
+		/// The grams of CO₂ equivalent emitted per kilometre by a transport mode changed. [mode, factor]
+		EmissionFactorSet(TransportMode, Option<u32>),
+		/// A completed leg was charged its estimated emissions. [shipment_uid, leg, carrier, emissions]
+		LegEmissionsRecorded(u64, u8, T::AccountId, u64),
+		/// A shipment has not reached a hop by its planned arrival. [shipment_uid, hop, planned_arrival]
+		ShipmentLate(u64, u8, T::BlockNumber),
+		/// The capacity of a transit node changed. [transit_node, capacity]
+		CapacitySet(T::AccountId, Option<u32>),
+		/// The destination accepted a shipment. [shipment_uid, destination]
+		ShipmentAccepted(u64, T::AccountId),
+		/// The destination rejected a shipment, which returns to its creator. [shipment_uid, reason, return_uid]
+		ShipmentRejected(u64, RejectionReason, u64),
+		/// The creator cancelled a shipment, compensating its holder. [shipment_uid, holder, penalty]
+		ShipmentCancelled(u64, T::AccountId, BalanceOf<T>),
+		/// The creator changed the destination of a shipment. [shipment_uid, old_destination, new_destination]
+		DestinationAmended(u64, T::AccountId, T::AccountId),
+		/// A finalized shipment left storage, leaving the hash of its record. [shipment_uid, record_hash]
+		ShipmentArchived(u64, T::Hash),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		InvalidUID,
+		InvalidShipmentUID,
+		InvalidRoute,
+		InvalidKey,
+		KeyNotFound,
+		ShipmentAlreadyExists,
+		ShipmentKeyAlreadyExists,
+		ShipmentNotFound,
+		TransitPointAlreadyExists,
+		TransitNodesOverFlow,
+		TransitPointNotFound,
+		UIDNotFound,
+		UnauthorizedCaller,
+		CallerIsNotFirstNode,
+		RouteSearchLimitReached,
+		ShipmentNotInTransit,
+		ShipmentDisputed,
+		DisputeAlreadyOpen,
+		DisputeNotFound,
+		MissingCommitment,
+		NotOperator,
+		NotNodeOperator,
+		OperatorAlreadyRegistered,
+		OperatorNotFound,
+		NotBonded,
+		StillTransitNode,
+		StillUnbonding,
+		CustodyOutstanding,
+		NotBonder,
+		EmptyContent,
+		MassTooHigh,
+		VolumeTooHigh,
+		DeclaredValueTooHigh,
+		UnknownHandlingFlags,
+		ShipmentPacked,
+		InvalidChild,
+		HolderMismatch,
+		TooManyChildren,
+		NotPacked,
+		TelemetryTooFrequent,
+		StaleTelemetry,
+		InvalidConditionLimits,
+		PenaltyTooHigh,
+		NotNeighbours,
+		NodeSaturated,
+		NotAwaitingAcceptance,
+		CannotRejectReturn,
+		ContainsPackedShipments,
+		InvalidDestination,
+		InvalidObjective,
+		TooManyLegEmissions,
+		TooManyEdges,
+	}
+
+	// (from, to) -> cost, transit time and profile of the edge in that direction. Lanes without a
+	// reverse entry are one-way
+	#[pallet::storage]
+	#[pallet::getter(fn edge)]
+	pub(super) type Edges<T:Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AccountId,
+		EdgeAttributes<T>,
+		OptionQuery,
+	>;
+
+	// transport mode -> grams of CO₂ equivalent emitted per kilometre carrying a shipment
+	#[pallet::storage]
+	#[pallet::getter(fn emission_factor)]
+	pub(super) type EmissionFactors<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		TransportMode,
+		u32,
+		ValueQuery,
+	>;
+
+	// shipment_uid -> (leg, estimated emissions, block completed) of every leg completed on its own
+	#[pallet::storage]
+	#[pallet::getter(fn leg_emissions)]
+	pub(super) type LegEmissions<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		BoundedVec<(u8, u64, T::BlockNumber), T::MaxSize>,
+		ValueQuery,
+	>;
+
+	// shipment_uid -> estimated emissions of its completed legs
+	#[pallet::storage]
+	#[pallet::getter(fn shipment_emissions)]
+	pub(super) type ShipmentEmissions<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		u64,
+		ValueQuery,
+	>;
+
+	// transit_node -> estimated emissions of every leg it carried
+	#[pallet::storage]
+	#[pallet::getter(fn node_emissions)]
+	pub(super) type NodeEmissions<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		u64,
+		ValueQuery,
+	>;
+
+	// (account, block, shipment_uid) -> emissions of the legs completed in the block that the
+	// account is accountable for (as creator, as carrier), until the shipment is archived
+	#[pallet::storage]
+	#[pallet::getter(fn emissions_ledger)]
+	pub(super) type EmissionsLedger<T:Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Blake2_128Concat, T::AccountId>,
+			NMapKey<Twox64Concat, T::BlockNumber>,
+			NMapKey<Twox64Concat, u64>,
+		),
+		(u64, u64),
+		ValueQuery,
+	>;
+
+	// (origin, dest) -> route generation it was planned in and the cheapest route of the lane
+	#[pallet::storage]
+	#[pallet::getter(fn route_vec)]
+	pub(super) type RouteVector<T:Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AccountId,
+		(u32, BoundedVec<T::AccountId, T::MaxSize>),
+		OptionQuery,
+	>;
+
+	// Bumped on every change to the transit network. Cached routes of older generations are stale.
+	#[pallet::storage]
+	#[pallet::getter(fn route_generation)]
+	pub(super) type RouteGeneration<T> = StorageValue<_, u32, ValueQuery>;
+
+	// shipment_uid -> commitment to the secret of the next handoff
+	#[pallet::storage]
+	#[pallet::getter(fn shipment_commitment)]
+	pub(super) type UIDToCommitment<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		T::Hash,
+		OptionQuery,
+	>;
+
+	// shipment_uid -> shipment map
+	#[pallet::storage]
+	#[pallet::getter(fn uid_to_shipment)]
+	pub(super) type UIDToShipment<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		Shipment<T>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn shipment_uid)]
+	pub(super) type ShipmentUID<T:Config> = StorageValue<
+		_,
+		u64,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	pub(super) type TransitNodes<T:Config> = CountedStorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		(),
+		OptionQuery,
+	>;
+
+	// (block_number, shipment_uid) -> () for shipments whose current leg expires at that block
+	#[pallet::storage]
+	#[pallet::getter(fn deadlines)]
+	pub(super) type Deadlines<T:Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		Blake2_128Concat,
+		u64,
+		(),
+		OptionQuery,
+	>;
+
+	// First block whose deadlines have not all been expired yet
+	#[pallet::storage]
+	#[pallet::getter(fn deadline_cursor)]
+	pub(super) type DeadlineCursor<T:Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+
+	// (transit_node, shipment_uid) -> () for unpacked in-transit shipments whose route passes
+	// the node from their current holder on
+	#[pallet::storage]
+	#[pallet::getter(fn shipments_by_route_node)]
+	pub(super) type ShipmentsByRouteNode<T:Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		u64,
+		(),
+		OptionQuery,
+	>;
+
+	// position -> (change to the transit network whose shipments still have to be rerouted, operator that made it)
+	#[pallet::storage]
+	#[pallet::getter(fn reroute_queue)]
+	pub(super) type RerouteQueue<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		(NetworkChange<T::AccountId>, Option<T::AccountId>),
+		OptionQuery,
+	>;
+
+	// Position of the oldest change still being rerouted
+	#[pallet::storage]
+	#[pallet::getter(fn reroute_head)]
+	pub(super) type RerouteHead<T> = StorageValue<_, u64, ValueQuery>;
+
+	// Position at which the next change is queued
+	#[pallet::storage]
+	#[pallet::getter(fn reroute_tail)]
+	pub(super) type RerouteTail<T> = StorageValue<_, u64, ValueQuery>;
+
+	// Last shipment looked at for the oldest queued change
+	#[pallet::storage]
+	#[pallet::getter(fn reroute_cursor)]
+	pub(super) type RerouteCursor<T> = StorageValue<_, u64, OptionQuery>;
+
+	// transit_node -> maximum number of shipments held or inbound
+	#[pallet::storage]
+	#[pallet::getter(fn capacity)]
+	pub(super) type Capacity<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		u32,
+		OptionQuery,
+	>;
+
+	// transit_node -> number of in-transit shipments it holds
+	#[pallet::storage]
+	#[pallet::getter(fn custody_count)]
+	pub(super) type CustodyCount<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		u32,
+		ValueQuery,
+	>;
+
+	// (transit_node, shipment_uid) -> planned arrival of a shipment whose next hop is the node
+	#[pallet::storage]
+	#[pallet::getter(fn inbound_queue)]
+	pub(super) type InboundQueue<T:Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		u64,
+		T::BlockNumber,
+		OptionQuery,
+	>;
+
+	// transit_node -> number of entries in its inbound queue
+	#[pallet::storage]
+	#[pallet::getter(fn inbound_count)]
+	pub(super) type InboundCount<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		u32,
+		ValueQuery,
+	>;
+
+	// (creator, shipment_uid) -> () for every shipment the account created
+	#[pallet::storage]
+	pub(super) type ShipmentsByCreator<T:Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		u64,
+		(),
+		OptionQuery,
+	>;
+
+	// (destination, shipment_uid) -> () for every shipment bound for the account
+	#[pallet::storage]
+	pub(super) type ShipmentsByDestination<T:Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		u64,
+		(),
+		OptionQuery,
+	>;
+
+	// (holder, shipment_uid) -> () for every unpacked in-transit shipment the account holds
+	#[pallet::storage]
+	pub(super) type ShipmentsByHolder<T:Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		u64,
+		(),
+		OptionQuery,
+	>;
+
+	// shipment_uid -> block at which each hop of the route is planned to be reached,
+	// starting with route[1]
+	#[pallet::storage]
+	#[pallet::getter(fn planned_arrivals)]
+	pub(super) type PlannedArrivals<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		BoundedVec<T::BlockNumber, T::MaxSize>,
+		ValueQuery,
+	>;
+
+	// (block_number, shipment_uid) -> planned arrival for shipments whose current leg is checked
+	// for lateness at that block
+	#[pallet::storage]
+	#[pallet::getter(fn arrival_checks)]
+	pub(super) type ArrivalChecks<T:Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		Blake2_128Concat,
+		u64,
+		T::BlockNumber,
+		OptionQuery,
+	>;
+
+	// First block whose arrival checks have not all been made yet
+	#[pallet::storage]
+	#[pallet::getter(fn arrival_cursor)]
+	pub(super) type ArrivalCursor<T:Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+
+	// shipment_uid -> why its destination rejected it and the uid of its return shipment
+	#[pallet::storage]
+	#[pallet::getter(fn rejections)]
+	pub(super) type Rejections<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		(RejectionReason, u64),
+		OptionQuery,
+	>;
+
+	// return_uid -> uid of the rejected shipment it returns
+	#[pallet::storage]
+	#[pallet::getter(fn return_of)]
+	pub(super) type ReturnOf<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		u64,
+		OptionQuery,
+	>;
+
+	// shipment_uid -> block at which the dispute was opened
+	#[pallet::storage]
+	#[pallet::getter(fn disputes)]
+	pub(super) type Disputes<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		T::BlockNumber,
+		OptionQuery,
+	>;
+
+	// registered operators
+	#[pallet::storage]
+	#[pallet::getter(fn operators)]
+	pub(super) type Operators<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		(),
+		OptionQuery,
+	>;
+
+	// transit_node -> operator managing it
+	#[pallet::storage]
+	#[pallet::getter(fn node_operator)]
+	pub(super) type NodeOperator<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		T::AccountId,
+		OptionQuery,
+	>;
+
+	// shipment_uid -> description of the goods
+	#[pallet::storage]
+	#[pallet::getter(fn shipment_metadata)]
+	pub(super) type UIDToMetadata<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		ShipmentMetadata<T>,
+		OptionQuery,
+	>;
+
+	// parent_uid -> shipments packed into it
+	#[pallet::storage]
+	#[pallet::getter(fn children)]
+	pub(super) type Children<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		BoundedVec<u64, T::MaxChildren>,
+		ValueQuery,
+	>;
+
+	// shipment_uid -> condition limits
+	#[pallet::storage]
+	#[pallet::getter(fn shipment_conditions)]
+	pub(super) type UIDToConditions<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		ConditionLimits<T>,
+		OptionQuery,
+	>;
+
+	// shipment_uid -> hops during which the condition limits were breached
+	#[pallet::storage]
+	#[pallet::getter(fn breaches)]
+	pub(super) type Breaches<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		BoundedVec<u8, T::MaxSize>,
+		ValueQuery,
+	>;
+
+	// shipment_uid -> last reported telemetry
+	#[pallet::storage]
+	#[pallet::getter(fn latest_telemetry)]
+	pub(super) type LatestTelemetry<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		TelemetryReading,
+		OptionQuery,
+	>;
+
+	// shipment_uid -> first block at which a new reading is accepted
+	#[pallet::storage]
+	#[pallet::getter(fn next_telemetry_at)]
+	pub(super) type NextTelemetryAt<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		T::BlockNumber,
+		ValueQuery,
+	>;
+
+	// child_uid -> parent_uid
+	#[pallet::storage]
+	#[pallet::getter(fn parent_of)]
+	pub(super) type ParentOf<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		u64,
+		OptionQuery,
+	>;
+
+	// shipment_uid -> custody history, pruned once the shipment is delivered or failed
+	#[pallet::storage]
+	#[pallet::getter(fn custody_log)]
+	pub(super) type CustodyLog<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		BoundedVec<CustodyRecord<T>, T::MaxSize>,
+		ValueQuery,
+	>;
+
+	// transit_node -> bond still reserved
+	#[pallet::storage]
+	#[pallet::getter(fn bonds)]
+	pub(super) type Bonds<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		BalanceOf<T>,
+		OptionQuery,
+	>;
+
+	// transit_node -> account its bond is reserved from, if not the node itself
+	#[pallet::storage]
+	#[pallet::getter(fn bonded_by)]
+	pub(super) type BondedBy<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		T::AccountId,
+		OptionQuery,
+	>;
+
+	// removed transit_node -> block from which its bond can be withdrawn
+	#[pallet::storage]
+	#[pallet::getter(fn unbonding)]
+	pub(super) type Unbonding<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		T::BlockNumber,
+		OptionQuery,
+	>;
+
+	// archival position -> (shipment_uid, block at which the shipment was finalized)
+	#[pallet::storage]
+	#[pallet::getter(fn archival_queue)]
+	pub(super) type ArchivalQueue<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		(u64, T::BlockNumber),
+		OptionQuery,
+	>;
+
+	// Position of the next shipment to archive
+	#[pallet::storage]
+	#[pallet::getter(fn archival_head)]
+	pub(super) type ArchivalHead<T> = StorageValue<_, u64, ValueQuery>;
+
+	// Position at which the next finalized shipment is queued
+	#[pallet::storage]
+	#[pallet::getter(fn archival_tail)]
+	pub(super) type ArchivalTail<T> = StorageValue<_, u64, ValueQuery>;
+
+	// shipment_uid -> hash of the records removed when the shipment was archived
+	#[pallet::storage]
+	#[pallet::getter(fn archived_shipments)]
+	pub(super) type ArchivedShipments<T:Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u64,
+		T::Hash,
+		OptionQuery,
+	>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Transit nodes in registration order, each with edges to nodes listed before it.
+		pub transit_nodes: Vec<(T::AccountId, Vec<(T::AccountId, u32)>)>,
+		/// Shipments created at genesis as (creator, destination, first handoff commitment).
+		pub shipments: Vec<(T::AccountId, T::AccountId, T::Hash)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { transit_nodes: Default::default(), shipments: Default::default() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (transit_node, neighbours) in &self.transit_nodes {
+				assert!(neighbours.len() <= T::MaxSize::get() as usize, "Transit node has too many neighbours");
+				Pallet::<T>::do_create_transit_node(transit_node.clone(), transit_node, neighbours)
+					.expect("Genesis transit nodes must be unique and only reference earlier nodes");
+			}
+			for (creator, destination, commitment) in &self.shipments {
+				Pallet::<T>::do_create_shipment(creator.clone(), creator.clone(), destination.clone(), *commitment, None, None, RouteObjective::Cheapest)
+					.expect("Genesis shipments must be routable and affordable by their creator");
+			}
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let mut reads_writes: u64 = 2;
+
+			// Expire at most `MaxDeadlinesPerBlock` deadlines from the cursor on. The cursor stays
+			// on a block until all of its deadlines are expired, spilling them over to later blocks.
+			let mut budget = T::MaxDeadlinesPerBlock::get() as usize;
+			let mut cursor = Self::deadline_cursor();
+			while cursor <= now && budget > 0 {
+				let expiring: Vec<u64> = Deadlines::<T>::drain_prefix(cursor)
+					.take(budget)
+					.map(|(uid, ())| uid)
+					.collect();
+				reads_writes = reads_writes.saturating_add(1);
+				if expiring.len() < budget {
+					cursor = cursor.saturating_add(One::one());
+				}
+				budget = budget.saturating_sub(expiring.len());
+
+				for uid in expiring.iter() {
+					reads_writes = reads_writes.saturating_add(3);
+					let mut shipment = match Self::uid_to_shipment(uid) {
+						Some(shipment) => shipment,
+						None => continue,
+					};
+					// Disputed shipments wait for the arbiter, packed ones follow their parent
+					if shipment.status != ShipmentStatus::InTransit
+						|| Disputes::<T>::contains_key(uid)
+						|| ParentOf::<T>::contains_key(uid) {
+						continue;
+					}
+					let leg = shipment.owner_index.saturating_sub(1) as usize;
+					if shipment.leg_deadlines.get(leg).map_or(false, |deadline| *deadline <= now) {
+						reads_writes = reads_writes.saturating_add(3);
+						Self::slash_holder(&shipment, T::FailureSlash::get());
+						// Failing a shipment also fails every shipment packed into it
+						let failed = Self::fail_shipment(&mut shipment);
+						reads_writes = reads_writes.saturating_add(FAIL_SHIPMENT_READS_WRITES.saturating_mul(failed as u64));
+						Self::deposit_event(Event::ShipmentTimedOut(*uid));
+					}
+				}
+			}
+			DeadlineCursor::<T>::put(cursor);
+
+			let rerouted = Self::process_reroutes(T::MaxReroutesPerBlock::get());
+			let reroute_weight = Self::reroute_weight().saturating_mul(rerouted as Weight);
+
+			// Arrival checks spill over to later blocks the same way, on a budget of their own
+			let mut budget = T::MaxDeadlinesPerBlock::get() as usize;
+			let mut cursor = Self::arrival_cursor();
+			while cursor <= now && budget > 0 {
+				let checks: Vec<(u64, T::BlockNumber)> = ArrivalChecks::<T>::drain_prefix(cursor)
+					.take(budget)
+					.collect();
+				reads_writes = reads_writes.saturating_add(1);
+				if checks.len() < budget {
+					cursor = cursor.saturating_add(One::one());
+				}
+				budget = budget.saturating_sub(checks.len());
+
+				for (uid, planned) in checks.iter() {
+					reads_writes = reads_writes.saturating_add(3);
+					let shipment = match Self::uid_to_shipment(uid) {
+						Some(shipment) if shipment.status == ShipmentStatus::InTransit => shipment,
+						_ => continue,
+					};
+					if ParentOf::<T>::contains_key(uid) {
+						continue;
+					}
+					// Checks left behind by a reroute no longer match the plan
+					let leg = shipment.owner_index.saturating_sub(1) as usize;
+					if Self::planned_arrivals(uid).get(leg) == Some(planned) {
+						Self::deposit_event(Event::ShipmentLate(*uid, shipment.owner_index, *planned));
+					}
+				}
+			}
+			ArrivalCursor::<T>::put(cursor);
+
+			T::DbWeight::get().reads_writes(reads_writes, reads_writes).saturating_add(reroute_weight)
+		}
+
+		/// Archives finalized shipments whose retention period has passed, oldest first, for
+		/// as long as the remaining weight allows.
+		fn on_idle(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			let per_shipment = T::DbWeight::get().reads_writes(12, 18);
+			let mut used = T::DbWeight::get().reads_writes(2, 1);
+			if used > remaining_weight {
+				return 0;
+			}
+
+			let mut head = Self::archival_head();
+			while head < Self::archival_tail() && used.saturating_add(per_shipment) <= remaining_weight {
+				used = used.saturating_add(per_shipment);
+				let (uid, finalized_at) = match ArchivalQueue::<T>::get(head) {
+					Some(entry) => entry,
+					None => {
+						head = head.saturating_add(1);
+						continue;
+					},
+				};
+				if finalized_at.saturating_add(T::RetentionPeriod::get()) > now {
+					break;
+				}
+				// Every leg completed on its own left a ledger entry for its creator and its carrier
+				let legs = LegEmissions::<T>::decode_len(uid).unwrap_or(0) as Weight;
+				let ledger = T::DbWeight::get().writes(legs.saturating_mul(2));
+				if used.saturating_add(ledger) > remaining_weight {
+					break;
+				}
+				used = used.saturating_add(ledger);
+				ArchivalQueue::<T>::remove(head);
+				head = head.saturating_add(1);
+				Self::archive_shipment(uid);
+			}
+			ArchivalHead::<T>::put(head);
+
+			used
+		}
+
+		fn offchain_worker(now: T::BlockNumber) {
+			if let Err(error) = Self::ingest_telemetry(now) {
+				log::debug!(target: "runtime::asset-tracking", "Telemetry not ingested: {}", error);
+			}
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			let (payload, signature) = match call {
+				Call::submit_telemetry { payload, signature } => (payload, signature),
+				_ => return InvalidTransaction::Call.into(),
+			};
+			if !SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone()) {
+				return InvalidTransaction::BadProof.into();
+			}
+			Self::check_telemetry(payload).map_err(|error| match error {
+				Error::<T>::TelemetryTooFrequent => InvalidTransaction::Future,
+				Error::<T>::StaleTelemetry => InvalidTransaction::Stale,
+				_ => InvalidTransaction::BadSigner,
+			})?;
+			let next_at = Self::next_telemetry_at(payload.shipment_uid);
+
+			ValidTransaction::with_tag_prefix("AssetTrackingTelemetry")
+				.priority(T::TelemetryPriority::get())
+				// One reading per shipment and interval
+				.and_provides((payload.shipment_uid, next_at))
+				.longevity(5)
+				.propagate(true)
+				.build()
+		}
+	}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+
+		#[pallet::weight(T::WeightInfo::create_new_transit_node(neighbours.len() as u32))]
+		pub fn create_new_transit_node(
+			origin: OriginFor<T>,
+			transit_node: T::AccountId,
+			neighbours: BoundedVec<(T::AccountId, u32), T::MaxSize>
+		) -> DispatchResult {
+
+			let operator = Self::ensure_network_admin(origin)?;
+			for neighbour in neighbours.iter() {
+				Self::ensure_manages(&operator, &neighbour.0)?;
+			}
+
+			// Nodes created by an operator are bonded and managed by it
+			let bonder = operator.clone().unwrap_or_else(|| transit_node.clone());
+			Self::do_create_transit_node(transit_node.clone(), &bonder, &neighbours)?;
+			if let Some(operator) = operator {
+				NodeOperator::<T>::insert(&transit_node, &operator);
+				Self::deposit_event(Event::NodeOperatorSet(transit_node, Some(operator)));
+			}
+
+			Ok(())
+		}
+
+		/// Sets the cost of the edge between two nodes in both directions.
+		#[pallet::weight(T::WeightInfo::update_neighbour().saturating_add(Self::max_reroute_weight()))]
+		pub fn update_neighbour(
+			origin: OriginFor<T>,
+			node1: T::AccountId,
+			node2: T::AccountId,
+			cost: u32
+		) ->DispatchResult {
+			let operator = Self::ensure_network_admin(origin)?;
+			ensure!(Self::is_transit_node(&node1) && Self::is_transit_node(&node2), Error::<T>::TransitPointNotFound);
+			Self::ensure_manages(&operator, &node1)?;
+			Self::ensure_manages(&operator, &node2)?;
+			Self::ensure_edge_room(&node1, &node2)?;
+			Self::ensure_edge_room(&node2, &node1)?;
+
+			Self::set_edge_cost(&node1, &node2, cost);
+			Self::set_edge_cost(&node2, &node1, cost);
+			Self::invalidate_routes();
+			Self::queue_reroutes(NetworkChange::Lane(node1.clone(), node2.clone()), operator);
+
+			Self::deposit_event(Event::NeighbourUpdated(node1,node2));
+
+			Ok(())
+		}
+
+		/// Sets the cost of the edge from `from` to `to` only, leaving the opposite direction
+		/// as it is. `None` removes the edge in that direction, so that lanes can be one-way.
+		/// Shipments planned over a changed edge are rerouted.
+		#[pallet::weight(T::WeightInfo::set_directed_edge().saturating_add(Self::max_reroute_weight()))]
+		pub fn set_directed_edge(
+			origin: OriginFor<T>,
+			from: T::AccountId,
+			to: T::AccountId,
+			cost: Option<u32>
+		) -> DispatchResult {
+
+			let operator = Self::ensure_network_admin(origin)?;
+			ensure!(Self::is_transit_node(&from) && Self::is_transit_node(&to), Error::<T>::TransitPointNotFound);
+			ensure!(from != to, Error::<T>::InvalidRoute);
+			Self::ensure_manages(&operator, &from)?;
+			Self::ensure_manages(&operator, &to)?;
+
+			match cost {
+				Some(cost) => {
+					Self::ensure_edge_room(&from, &to)?;
+					Self::set_edge_cost(&from, &to, cost);
+				},
+				None => {
+					ensure!(Edges::<T>::contains_key(&from, &to), Error::<T>::NotNeighbours);
+					Edges::<T>::remove(&from, &to);
+				},
+			}
+			Self::invalidate_routes();
+			Self::queue_reroutes(NetworkChange::Edge(from.clone(), to.clone()), operator);
+
+			Self::deposit_event(Event::DirectedEdgeSet(from, to, cost));
+
+			Ok(())
+		}
+
+		/// Sets how many blocks the edge between two neighbours is expected to take, used for
+		/// ETAs. Edges without a duration are expected to take `LegTimeout`. Applies to the
+		/// opposite direction too, unless the edge is one-way. Shipments planned over the
+		/// lane are rerouted.
+		#[pallet::weight(T::WeightInfo::set_edge_duration().saturating_add(Self::max_reroute_weight()))]
+		pub fn set_edge_duration(
+			origin: OriginFor<T>,
+			node1: T::AccountId,
+			node2: T::AccountId,
+			duration: Option<T::BlockNumber>
+		) -> DispatchResult {
+
+			let operator = Self::ensure_network_admin(origin)?;
+			ensure!(Edges::<T>::contains_key(&node1, &node2), Error::<T>::NotNeighbours);
+			Self::ensure_manages(&operator, &node1)?;
+			Self::ensure_manages(&operator, &node2)?;
+
+			Self::update_lane(&node1, &node2, |edge| edge.transit_time = duration);
+			Self::invalidate_routes();
+			Self::queue_reroutes(NetworkChange::Lane(node1.clone(), node2.clone()), operator);
+
+			Self::deposit_event(Event::EdgeDurationSet(node1, node2, duration));
+
+			Ok(())
+		}
+
+		/// Sets the distance and transport mode of the edge between two neighbours, used
+		/// by the greenest and weighted route objectives. Applies to the opposite direction
+		/// too, unless the edge is one-way. Shipments planned over the lane are rerouted.
+		#[pallet::weight(T::WeightInfo::set_edge_profile().saturating_add(Self::max_reroute_weight()))]
+		pub fn set_edge_profile(
+			origin: OriginFor<T>,
+			node1: T::AccountId,
+			node2: T::AccountId,
+			profile: Option<EdgeProfile>
+		) -> DispatchResult {
+
+			let operator = Self::ensure_network_admin(origin)?;
+			ensure!(Edges::<T>::contains_key(&node1, &node2), Error::<T>::NotNeighbours);
+			Self::ensure_manages(&operator, &node1)?;
+			Self::ensure_manages(&operator, &node2)?;
+
+			Self::update_lane(&node1, &node2, |edge| edge.profile = profile);
+			Self::invalidate_routes();
+			Self::queue_reroutes(NetworkChange::Lane(node1.clone(), node2.clone()), operator);
+
+			Self::deposit_event(Event::EdgeProfileSet(node1, node2, profile));
+
+			Ok(())
+		}
+
+		/// Sets the grams of CO₂ equivalent a transport mode emits per kilometre carrying a
+		/// shipment. Modes without a factor are treated as emission-free.
+		#[pallet::weight(T::WeightInfo::set_emission_factor())]
+		pub fn set_emission_factor(origin: OriginFor<T>, mode: TransportMode, factor: Option<u32>) -> DispatchResult {
+
+			T::NetworkAdminOrigin::ensure_origin(origin)?;
+
+			match factor {
+				Some(factor) => EmissionFactors::<T>::insert(mode, factor),
+				None => EmissionFactors::<T>::remove(mode),
+			}
+
+			Self::deposit_event(Event::EmissionFactorSet(mode, factor));
+
+			Ok(())
+		}
+
+		#[pallet::weight(
+			T::WeightInfo::remove_transit_node(Self::count_for_transit_point() as u32)
+				.saturating_add(Self::max_reroute_weight())
+		)]
+		pub fn remove_transit_node(origin: OriginFor<T>, transit_node: T::AccountId) -> DispatchResult {
+
+			let operator = Self::ensure_network_admin(origin)?;
+			ensure!(Self::is_transit_node(&transit_node), Error::<T>::TransitPointNotFound);
+			Self::ensure_manages(&operator, &transit_node)?;
+			// Failing the shipments it holds would spare the node its bond
+			ensure!(Self::custody_count(&transit_node) == 0, Error::<T>::CustodyOutstanding);
+
+			Edges::<T>::remove_prefix(&transit_node, None);
+			for node in TransitNodes::<T>::iter_keys() {
+				if node != transit_node {
+					Edges::<T>::remove(&node, &transit_node);
+				}
+			}
+
+			TransitNodes::<T>::remove(&transit_node);
+			NodeOperator::<T>::remove(&transit_node);
+			Capacity::<T>::remove(&transit_node);
+			if Bonds::<T>::contains_key(&transit_node) {
+				let unlock_at = <frame_system::Pallet<T>>::block_number().saturating_add(T::UnbondingPeriod::get());
+				Unbonding::<T>::insert(&transit_node, unlock_at);
+			}
+			Self::invalidate_routes();
+			Self::queue_reroutes(NetworkChange::Node(transit_node.clone()), operator);
+
+			Self::deposit_event(Event::TransitPointRemoved(transit_node));
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::create_shipment().saturating_add(Self::max_route_search_weight()))]
+		pub fn create_shipment(
+			origin: OriginFor<T>,
+			destination: T::AccountId,
+			commitment: T::Hash,
+			sealed_secret: BoundedVec<u8, T::MaxSealedSecretLen>,
+			metadata: Option<ShipmentMetadata<T>>,
+			conditions: Option<ConditionLimits<T>>,
+			objective: RouteObjective
+		) -> DispatchResult {
+
+			let transit_node = ensure_signed(origin)?;
+			ensure!(!Self::is_saturated(&destination), Error::<T>::NodeSaturated);
+			let shipment = Self::do_create_shipment(
+				transit_node.clone(),
+				transit_node,
+				destination,
+				commitment,
+				metadata,
+				conditions,
+				objective
+			)?;
+			Self::seal_secret(&shipment, sealed_secret);
+
+			Ok(())
+		}
+
+		/// Hands a shipment over to the caller, the next hop of its route. Nothing is paid or
+		/// stored if any step fails.
+		#[pallet::weight(T::WeightInfo::update_shipment())]
+		#[transactional]
+		pub fn update_shipment(
+			origin: OriginFor<T>,
+			shipment_uid: u64,
+			secret: [u8; 32],
+			next_commitment: Option<T::Hash>,
+			sealed_secret: BoundedVec<u8, T::MaxSealedSecretLen>,
+			note: Option<T::Hash>
+		) -> DispatchResult {
+
+			let transit_node = ensure_signed(origin)?;
+			let mut shipment = Self::uid_to_shipment(shipment_uid).ok_or(Error::<T>::ShipmentNotFound)?;
+			ensure!(shipment.status == ShipmentStatus::InTransit, Error::<T>::ShipmentNotInTransit);
+
+			let commitment = Self::shipment_commitment(&shipment_uid).ok_or(Error::<T>::UIDNotFound)?;
+			ensure!(
+				Self::handoff_commitment(shipment_uid, shipment.owner_index, &secret) == commitment,
+				Error::<T>::InvalidKey);
+			let next_hop = shipment.route.get(shipment.owner_index as usize).ok_or(Error::<T>::UnauthorizedCaller)?;
+			ensure!(&transit_node == next_hop, Error::<T>::UnauthorizedCaller);
+			ensure!(!Disputes::<T>::contains_key(&shipment_uid), Error::<T>::ShipmentDisputed);
+			ensure!(!ParentOf::<T>::contains_key(&shipment_uid), Error::<T>::ShipmentPacked);
+			ensure!(transit_node == shipment.destination || next_commitment.is_some(), Error::<T>::MissingCommitment);
+
+			UIDToCommitment::<T>::remove(&shipment_uid);
+			Self::release_custody(&shipment);
+
+			// The caller has completed the leg into its own node
+			Self::pay_leg(&shipment, shipment.owner_index as usize - 1, &transit_node)?;
+			Self::record_leg_emissions(&shipment, shipment.owner_index as usize - 1)?;
+			Self::log_custody(&shipment, shipment.owner_index, &transit_node, note)?;
+
+			match transit_node == shipment.destination {
+				true => {
+					// Shipment has reached end destination, which decides whether to accept it
+					shipment.owner_index = 0;
+					shipment.status = ShipmentStatus::AwaitingAcceptance;
+					UIDToShipment::<T>::insert(&shipment_uid, &shipment);
+					CustodyLog::<T>::remove(&shipment_uid);
+					Self::deposit_event(Event::ShipmentReceived(transit_node));
+				},
+				false => {
+					// Shipment is still in transit
+					shipment.owner_index = shipment.owner_index + 1;
+					Self::schedule_deadline(&shipment);
+					// The new holder commits to the secret it hands to the next hop
+					if let Some(next_commitment) = next_commitment {
+						UIDToCommitment::<T>::insert(&shipment_uid, &next_commitment);
+					}
+					UIDToShipment::<T>::insert(&shipment_uid, &shipment);
+					Self::take_custody(&shipment);
+					Self::deposit_event(Event::ShipmentUpdated(transit_node));
+					Self::seal_secret(&shipment, sealed_secret);
+				}
+			}
+
+			Ok(())
+		}
+
+		/// Confirms receipt of a shipment that reached the caller, its destination.
+		#[pallet::weight(T::WeightInfo::accept_shipment())]
+		pub fn accept_shipment(origin: OriginFor<T>, shipment_uid: u64) -> DispatchResult {
+
+			let destination = ensure_signed(origin)?;
+			let mut shipment = Self::uid_to_shipment(shipment_uid).ok_or(Error::<T>::ShipmentNotFound)?;
+
+			ensure!(shipment.destination == destination, Error::<T>::UnauthorizedCaller);
+			ensure!(shipment.status == ShipmentStatus::AwaitingAcceptance, Error::<T>::NotAwaitingAcceptance);
+
+			shipment.status = ShipmentStatus::Delivered;
+			UIDToShipment::<T>::insert(&shipment_uid, &shipment);
+			Self::queue_archival(shipment_uid);
+
+			Self::deposit_event(Event::ShipmentAccepted(shipment_uid, destination));
+
+			Ok(())
+		}
+
+		/// Refuses a shipment that reached the caller, its destination, and sends it back to
+		/// its creator as a new return shipment, paid for by the creator. The caller holds the
+		/// return first and commits to the secret of its first handoff. Returns cannot
+		/// themselves be rejected.
+		#[pallet::weight(T::WeightInfo::reject_shipment().saturating_add(Self::max_route_search_weight()))]
+		pub fn reject_shipment(
+			origin: OriginFor<T>,
+			shipment_uid: u64,
+			reason: RejectionReason,
+			commitment: T::Hash,
+			sealed_secret: BoundedVec<u8, T::MaxSealedSecretLen>
+		) -> DispatchResult {
+
+			let destination = ensure_signed(origin)?;
+			let mut shipment = Self::uid_to_shipment(shipment_uid).ok_or(Error::<T>::ShipmentNotFound)?;
+
+			ensure!(shipment.destination == destination, Error::<T>::UnauthorizedCaller);
+			ensure!(shipment.status == ShipmentStatus::AwaitingAcceptance, Error::<T>::NotAwaitingAcceptance);
+			ensure!(!ReturnOf::<T>::contains_key(&shipment_uid), Error::<T>::CannotRejectReturn);
+			ensure!(Self::children(&shipment_uid).is_empty(), Error::<T>::ContainsPackedShipments);
+
+			let returned = Self::do_create_shipment(
+				shipment.creator.clone(),
+				destination,
+				shipment.creator.clone(),
+				commitment,
+				Self::shipment_metadata(&shipment_uid),
+				Self::shipment_conditions(&shipment_uid),
+				shipment.objective
+			)?;
+
+			shipment.status = ShipmentStatus::Rejected;
+			UIDToShipment::<T>::insert(&shipment_uid, &shipment);
+			Self::queue_archival(shipment_uid);
+			Rejections::<T>::insert(&shipment_uid, (reason, returned.uid));
+			ReturnOf::<T>::insert(&returned.uid, shipment_uid);
+			Self::seal_secret(&returned, sealed_secret);
+
+			Self::deposit_event(Event::ShipmentRejected(shipment_uid, reason, returned.uid));
+
+			Ok(())
+		}
+
+		/// Withdraws an in-transit shipment and refunds the fees of its remaining legs. Once
+		/// the shipment has left its origin, `CancellationPenalty` of them goes to its holder.
+		/// The cancelled shipment is archived like any other finalized one.
+		#[pallet::weight(T::WeightInfo::cancel_shipment())]
+		pub fn cancel_shipment(origin: OriginFor<T>, shipment_uid: u64) -> DispatchResult {
+
+			let creator = ensure_signed(origin)?;
+			let mut shipment = Self::uid_to_shipment(shipment_uid).ok_or(Error::<T>::ShipmentNotFound)?;
+
+			ensure!(shipment.creator == creator, Error::<T>::UnauthorizedCaller);
+			ensure!(shipment.status == ShipmentStatus::InTransit, Error::<T>::ShipmentNotInTransit);
+			ensure!(!Disputes::<T>::contains_key(&shipment_uid), Error::<T>::ShipmentDisputed);
+			ensure!(!ParentOf::<T>::contains_key(&shipment_uid), Error::<T>::ShipmentPacked);
+			ensure!(Self::children(&shipment_uid).is_empty(), Error::<T>::ContainsPackedShipments);
+			let holder = shipment.current_holder().cloned().ok_or(Error::<T>::ShipmentNotInTransit)?;
+
+			let completed = (shipment.owner_index as usize).saturating_sub(1);
+			let remaining = shipment.leg_fees.iter().skip(completed)
+				.fold(BalanceOf::<T>::zero(), |total, fee| total.saturating_add(*fee));
+			let penalty = match completed > 0 && holder != creator {
+				true => T::CancellationPenalty::get().min(remaining),
+				false => Zero::zero(),
+			};
+			if !penalty.is_zero() {
+				T::Currency::repatriate_reserved(&creator, &holder, penalty, BalanceStatus::Free)?;
+			}
+			let refund = remaining.saturating_sub(penalty);
+			let not_refunded = T::Currency::unreserve(&creator, refund);
+
+			Self::release_custody(&shipment);
+			shipment.status = ShipmentStatus::Cancelled;
+			UIDToShipment::<T>::insert(&shipment_uid, &shipment);
+			Self::queue_archival(shipment_uid);
+			UIDToCommitment::<T>::remove(&shipment_uid);
+			CustodyLog::<T>::remove(&shipment_uid);
+
+			Self::deposit_event(Event::ShipmentFeesRefunded(shipment_uid, creator, refund.saturating_sub(not_refunded)));
+			Self::deposit_event(Event::ShipmentCancelled(shipment_uid, holder, penalty));
+
+			Ok(())
+		}
+
+		/// Sends an in-transit shipment to `new_destination` instead, re-planning the route
+		/// from its current holder. Fees are settled as for any reroute.
+		#[pallet::weight(T::WeightInfo::amend_destination().saturating_add(Self::reroute_search_weight()))]
+		pub fn amend_destination(origin: OriginFor<T>, shipment_uid: u64, new_destination: T::AccountId) -> DispatchResult {
+
+			let creator = ensure_signed(origin)?;
+			let mut shipment = Self::uid_to_shipment(shipment_uid).ok_or(Error::<T>::ShipmentNotFound)?;
+
+			ensure!(shipment.creator == creator, Error::<T>::UnauthorizedCaller);
+			ensure!(shipment.status == ShipmentStatus::InTransit, Error::<T>::ShipmentNotInTransit);
+			ensure!(!Disputes::<T>::contains_key(&shipment_uid), Error::<T>::ShipmentDisputed);
+			ensure!(!ParentOf::<T>::contains_key(&shipment_uid), Error::<T>::ShipmentPacked);
+			ensure!(Self::is_transit_node(&new_destination), Error::<T>::TransitPointNotFound);
+			ensure!(!Self::is_saturated(&new_destination), Error::<T>::NodeSaturated);
+			let holder = shipment.current_holder().cloned().ok_or(Error::<T>::ShipmentNotInTransit)?;
+			// The holder has to hand the shipment over at least once more
+			ensure!(new_destination != shipment.destination && new_destination != holder, Error::<T>::InvalidDestination);
+
+			let old_destination = sp_std::mem::replace(&mut shipment.destination, new_destination.clone());
+			Self::reroute(&mut shipment, &holder)?;
+			ShipmentsByDestination::<T>::remove(&old_destination, shipment_uid);
+			ShipmentsByDestination::<T>::insert(&new_destination, shipment_uid, ());
+
+			Self::deposit_event(Event::DestinationAmended(shipment_uid, old_destination, new_destination));
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::open_dispute())]
+		pub fn open_dispute(origin: OriginFor<T>, shipment_uid: u64) -> DispatchResult {
+
+			let creator = ensure_signed(origin)?;
+			let shipment = Self::uid_to_shipment(shipment_uid).ok_or(Error::<T>::ShipmentNotFound)?;
+
+			ensure!(shipment.creator == creator, Error::<T>::UnauthorizedCaller);
+			ensure!(shipment.status == ShipmentStatus::InTransit, Error::<T>::ShipmentNotInTransit);
+			ensure!(!Disputes::<T>::contains_key(&shipment_uid), Error::<T>::DisputeAlreadyOpen);
+			// Packed shipments have no handoffs of their own, so their parent is disputed instead
+			ensure!(!ParentOf::<T>::contains_key(&shipment_uid), Error::<T>::ShipmentPacked);
+
+			Disputes::<T>::insert(&shipment_uid, <frame_system::Pallet<T>>::block_number());
+
+			Self::deposit_event(Event::DisputeOpened(shipment_uid, creator));
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::resolve_dispute())]
+		pub fn resolve_dispute(origin: OriginFor<T>, shipment_uid: u64, outcome: DisputeOutcome) -> DispatchResult {
+
+			T::ArbiterOrigin::ensure_origin(origin)?;
+			ensure!(Disputes::<T>::contains_key(&shipment_uid), Error::<T>::DisputeNotFound);
+			let mut shipment = Self::uid_to_shipment(shipment_uid).ok_or(Error::<T>::ShipmentNotFound)?;
+
+			Disputes::<T>::remove(&shipment_uid);
+
+			match outcome {
+				DisputeOutcome::Creator => {
+					Self::slash_holder(&shipment, T::FailureSlash::get());
+					Self::fail_shipment(&mut shipment);
+				},
+				DisputeOutcome::Holder => {
+					// Give the holder a full leg from now if the deadline ran out during the dispute
+					let now = <frame_system::Pallet<T>>::block_number();
+					let leg = shipment.owner_index.saturating_sub(1) as usize;
+					let earliest = now.saturating_add(T::LegTimeout::get());
+					let delay = shipment.leg_deadlines.get(leg)
+						.map_or(Zero::zero(), |deadline| earliest.saturating_sub(*deadline));
+					if !delay.is_zero() {
+						let mut deadlines = shipment.leg_deadlines.to_vec();
+						for deadline in deadlines.iter_mut().skip(leg) {
+							*deadline = deadline.saturating_add(delay);
+						}
+						shipment.leg_deadlines = deadlines.try_into().map_err(|_| Error::<T>::TransitNodesOverFlow)?;
+						Self::schedule_deadline(&shipment);
+						UIDToShipment::<T>::insert(&shipment_uid, &shipment);
+					}
+				},
+			}
+
+			Self::deposit_event(Event::DisputeResolved(shipment_uid, outcome));
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::register_operator())]
+		pub fn register_operator(origin: OriginFor<T>, operator: T::AccountId) -> DispatchResult {
+
+			T::NetworkAdminOrigin::ensure_origin(origin)?;
+			ensure!(!Operators::<T>::contains_key(&operator), Error::<T>::OperatorAlreadyRegistered);
+
+			Operators::<T>::insert(&operator, ());
+
+			Self::deposit_event(Event::OperatorRegistered(operator));
+
+			Ok(())
+		}
+
+		/// Nodes keep their operator, which regains its rights if registered again.
+		#[pallet::weight(T::WeightInfo::deregister_operator())]
+		pub fn deregister_operator(origin: OriginFor<T>, operator: T::AccountId) -> DispatchResult {
+
+			T::NetworkAdminOrigin::ensure_origin(origin)?;
+			ensure!(Operators::<T>::contains_key(&operator), Error::<T>::OperatorNotFound);
+
+			Operators::<T>::remove(&operator);
+
+			Self::deposit_event(Event::OperatorDeregistered(operator));
+
+			Ok(())
+		}
+
+		/// Hands `transit_node` to a registered operator, or back to the admin with `None`.
+		#[pallet::weight(T::WeightInfo::set_node_operator())]
+		pub fn set_node_operator(
+			origin: OriginFor<T>,
+			transit_node: T::AccountId,
+			operator: Option<T::AccountId>
+		) -> DispatchResult {
+
+			T::NetworkAdminOrigin::ensure_origin(origin)?;
+			ensure!(Self::is_transit_node(&transit_node), Error::<T>::TransitPointNotFound);
+
+			match &operator {
+				Some(operator) => {
+					ensure!(Operators::<T>::contains_key(operator), Error::<T>::OperatorNotFound);
+					NodeOperator::<T>::insert(&transit_node, operator);
+				},
+				None => NodeOperator::<T>::remove(&transit_node),
+			}
+
+			Self::deposit_event(Event::NodeOperatorSet(transit_node, operator));
+
+			Ok(())
+		}
+
+		/// Limits how many shipments `transit_node` may hold or expect at once, as far as route
+		/// planning is concerned. `None` lifts the limit.
+		#[pallet::weight(T::WeightInfo::set_capacity())]
+		pub fn set_capacity(origin: OriginFor<T>, transit_node: T::AccountId, capacity: Option<u32>) -> DispatchResult {
+
+			T::NetworkAdminOrigin::ensure_origin(origin)?;
+			ensure!(Self::is_transit_node(&transit_node), Error::<T>::TransitPointNotFound);
+
+			match capacity {
+				Some(capacity) => Capacity::<T>::insert(&transit_node, capacity),
+				None => Capacity::<T>::remove(&transit_node),
+			}
+
+			Self::deposit_event(Event::CapacitySet(transit_node, capacity));
+
+			Ok(())
+		}
+
+		/// Releases the bond of a removed transit node to its bonder once its unbonding period
+		/// has passed and it no longer holds any shipment.
+		#[pallet::weight(T::WeightInfo::withdraw_bond())]
+		pub fn withdraw_bond(origin: OriginFor<T>, transit_node: T::AccountId) -> DispatchResult {
+
+			let who = ensure_signed(origin)?;
+			let bond = Self::bonds(&transit_node).ok_or(Error::<T>::NotBonded)?;
+			let bonder = Self::bonder_of(&transit_node);
+			ensure!(who == bonder, Error::<T>::NotBonder);
+			ensure!(!Self::is_transit_node(&transit_node), Error::<T>::StillTransitNode);
+			let unlock_at = Self::unbonding(&transit_node).ok_or(Error::<T>::StillTransitNode)?;
+			ensure!(<frame_system::Pallet<T>>::block_number() >= unlock_at, Error::<T>::StillUnbonding);
+			ensure!(Self::custody_count(&transit_node) == 0, Error::<T>::CustodyOutstanding);
+
+			let not_unreserved = T::Currency::unreserve(&bonder, bond);
+			Bonds::<T>::remove(&transit_node);
+			BondedBy::<T>::remove(&transit_node);
+			Unbonding::<T>::remove(&transit_node);
+
+			Self::deposit_event(Event::TransitNodeUnbonded(transit_node, bond.saturating_sub(not_unreserved)));
+
+			Ok(())
+		}
+
+		/// Packs shipments held by the caller into `parent_uid`, which the caller also holds.
+		/// Until unpacked, children move with the parent's handoffs. Containers do not nest.
+		#[pallet::weight(T::WeightInfo::pack_shipments(children.len() as u32))]
+		pub fn pack_shipments(
+			origin: OriginFor<T>,
+			parent_uid: u64,
+			children: BoundedVec<u64, T::MaxChildren>
+		) -> DispatchResult {
+
+			let holder = ensure_signed(origin)?;
+			let parent = Self::uid_to_shipment(parent_uid).ok_or(Error::<T>::ShipmentNotFound)?;
+			ensure!(parent.current_holder() == Some(&holder), Error::<T>::HolderMismatch);
+			ensure!(!ParentOf::<T>::contains_key(&parent_uid), Error::<T>::ShipmentPacked);
+
+			let mut unique = children.to_vec();
+			unique.sort();
+			unique.dedup();
+			ensure!(!children.is_empty() && unique.len() == children.len(), Error::<T>::InvalidChild);
+
+			for child_uid in children.iter() {
+				let child = Self::uid_to_shipment(child_uid).ok_or(Error::<T>::ShipmentNotFound)?;
+				ensure!(*child_uid != parent_uid && Self::children(child_uid).is_empty(), Error::<T>::InvalidChild);
+				ensure!(!ParentOf::<T>::contains_key(child_uid), Error::<T>::ShipmentPacked);
+				ensure!(!Disputes::<T>::contains_key(child_uid), Error::<T>::ShipmentDisputed);
+				ensure!(child.current_holder() == Some(&holder), Error::<T>::HolderMismatch);
+			}
+
+			Children::<T>::try_mutate(&parent_uid, |packed| {
+				children.iter().try_for_each(|child_uid| packed.try_push(*child_uid))
+			}).map_err(|_| Error::<T>::TooManyChildren)?;
+			for child_uid in children.iter() {
+				if let Some(child) = Self::uid_to_shipment(child_uid) {
+					Self::release_custody(&child);
+				}
+				ParentOf::<T>::insert(child_uid, parent_uid);
+			}
+
+			Self::deposit_event(Event::ShipmentsPacked(parent_uid, children));
+
+			Ok(())
+		}
+
+		/// Takes `child_uid` out of its parent at the parent's current holder, or at its
+		/// destination once arrived, and re-plans the child's route from there. The
+		/// caller commits to the secret of the child's next handoff. Nothing is refunded or
+		/// stored if any step fails.
+		#[pallet::weight(T::WeightInfo::unpack_shipment().saturating_add(Self::reroute_search_weight()))]
+		#[transactional]
+		pub fn unpack_shipment(
+			origin: OriginFor<T>,
+			child_uid: u64,
+			next_commitment: Option<T::Hash>,
+			sealed_secret: BoundedVec<u8, T::MaxSealedSecretLen>
+		) -> DispatchResult {
+
+			let transit_node = ensure_signed(origin)?;
+			let parent_uid = Self::parent_of(&child_uid).ok_or(Error::<T>::NotPacked)?;
+			let parent = Self::uid_to_shipment(parent_uid).ok_or(Error::<T>::ShipmentNotFound)?;
+			let mut child = Self::uid_to_shipment(child_uid).ok_or(Error::<T>::ShipmentNotFound)?;
+
+			let at_holder = parent.current_holder() == Some(&transit_node);
+			let at_destination = parent.has_arrived() && parent.destination == transit_node;
+			ensure!(at_holder || at_destination, Error::<T>::HolderMismatch);
+			ensure!(transit_node == child.destination || next_commitment.is_some(), Error::<T>::MissingCommitment);
+
+			if transit_node == child.destination {
+				Self::deliver_unpacked(&mut child, &transit_node)?;
+			} else {
+				Self::reroute(&mut child, &transit_node)?;
+				Self::log_custody(&child, child.owner_index.saturating_sub(1), &transit_node, None)?;
+				if let Some(next_commitment) = next_commitment {
+					UIDToCommitment::<T>::insert(&child_uid, &next_commitment);
+				}
+				Self::seal_secret(&child, sealed_secret);
+			}
+
+			ParentOf::<T>::remove(&child_uid);
+			Children::<T>::mutate(&parent_uid, |packed| packed.retain(|uid| *uid != child_uid));
+			Self::take_custody(&child);
+
+			Self::deposit_event(Event::ShipmentUnpacked(parent_uid, child_uid, transit_node));
+
+			Ok(())
+		}
+
+		/// Records a telemetry reading submitted by the offchain worker of the shipment's
+		/// holder. The signature is checked in `validate_unsigned`.
+		#[pallet::weight(T::WeightInfo::submit_telemetry())]
+		pub fn submit_telemetry(
+			origin: OriginFor<T>,
+			payload: TelemetryPayload<T>,
+			_signature: T::Signature
+		) -> DispatchResult {
+
+			ensure_none(origin)?;
+			// Re-checked, as several readings of one shipment can be valid in the same pool
+			let (shipment, holder) = Self::check_telemetry(&payload)?;
+			Self::record_reading(&shipment, holder, payload.reading);
+
+			Ok(())
+		}
+
+		/// Records a reading of the shipment's conditions, reported by its holder or by the
+		/// sensor named in its condition limits.
+		#[pallet::weight(T::WeightInfo::submit_reading())]
+		pub fn submit_reading(origin: OriginFor<T>, shipment_uid: u64, reading: TelemetryReading) -> DispatchResult {
+
+			let reporter = ensure_signed(origin)?;
+			let (shipment, holder) = Self::check_reporter(shipment_uid, &reporter)?;
+			Self::record_reading(&shipment, holder, reading);
+
+			Ok(())
+		}
+	}
+
+	// Helpful functions
+	impl<T: Config> Pallet<T> {
+
+		pub fn is_transit_node(account: &T::AccountId) -> bool {
+			TransitNodes::<T>::contains_key(account)
+		}
+
+		pub fn count_for_transit_point() -> u64 {
+			TransitNodes::<T>::count() as u64
+		}
+
+		/// Admin origins manage the whole network. Any other caller must be a registered
+		/// operator, which is returned so its rights over each node can be checked.
+		fn ensure_network_admin(origin: OriginFor<T>) -> Result<Option<T::AccountId>, DispatchError> {
+			match T::NetworkAdminOrigin::try_origin(origin) {
+				Ok(_) => Ok(None),
+				Err(origin) => {
+					let operator = ensure_signed(origin)?;
+					ensure!(Operators::<T>::contains_key(&operator), Error::<T>::NotOperator);
+					Ok(Some(operator))
+				},
+			}
+		}
+
+		/// Operators may only manage the transit nodes assigned to them.
+		fn ensure_manages(operator: &Option<T::AccountId>, transit_node: &T::AccountId) -> DispatchResult {
+			if let Some(operator) = operator {
+				ensure!(Self::node_operator(transit_node).as_ref() == Some(operator), Error::<T>::NotNodeOperator);
+			}
+			Ok(())
+		}
+
+		/// Registers `transit_node` with edges to already registered `neighbours`, reserving its
+		/// bond from `bonder`.
+		pub fn do_create_transit_node(
+			transit_node: T::AccountId,
+			bonder: &T::AccountId,
+			neighbours: &[(T::AccountId, u32)]
+		) -> DispatchResult {
+
+			ensure!(!Self::is_transit_node(&transit_node), Error::<T>::TransitPointAlreadyExists);
+			ensure!(
+				neighbours.iter().all(|neighbour| neighbour.0 != transit_node && Self::is_transit_node(&neighbour.0)),
+				Error::<T>::InvalidRoute);
+			ensure!(neighbours.len() as u32 <= T::MaxEdgesPerNode::get(), Error::<T>::TooManyEdges);
+			for neighbour in neighbours.iter() {
+				Self::ensure_edge_room(&neighbour.0, &transit_node)?;
+			}
+
+			// A node rejoining while unbonding only tops up what was slashed, from the same bonder
+			let bonded = Self::bonds(&transit_node);
+			ensure!(bonded.is_none() || Self::bonder_of(&transit_node) == *bonder, Error::<T>::NotBonder);
+			let bonded = bonded.unwrap_or_else(Zero::zero);
+			let top_up = T::TransitNodeBond::get().saturating_sub(bonded);
+			T::Currency::reserve(bonder, top_up)?;
+			Bonds::<T>::insert(&transit_node, bonded.saturating_add(top_up));
+			if *bonder != transit_node {
+				BondedBy::<T>::insert(&transit_node, bonder);
+			}
+			Unbonding::<T>::remove(&transit_node);
+
+			for neighbour in neighbours.iter() {
+				Edges::<T>::insert(&transit_node, &neighbour.0, EdgeAttributes::with_cost(neighbour.1));
+				Edges::<T>::insert(&neighbour.0, &transit_node, EdgeAttributes::with_cost(neighbour.1));
+			}
+
+			TransitNodes::<T>::insert(&transit_node, ());
+			Self::invalidate_routes();
+
+			if !top_up.is_zero() {
+				Self::deposit_event(Event::TransitNodeBonded(transit_node.clone(), bonder.clone(), top_up));
+			}
+			Self::deposit_event(Event::TransitPointCreated(transit_node));
+
+			Ok(())
+		}
+
+		/// Plans the route of a new shipment from `transit_node` for `objective`, reserves its
+		/// fees from `creator` and stores the commitment for the first handoff. Nothing is
+		/// reserved or stored if any step fails.
+		#[transactional]
+		pub fn do_create_shipment(
+			creator: T::AccountId,
+			transit_node: T::AccountId,
+			destination: T::AccountId,
+			commitment: T::Hash,
+			metadata: Option<ShipmentMetadata<T>>,
+			conditions: Option<ConditionLimits<T>>,
+			objective: RouteObjective
+		) -> Result<Shipment<T>, DispatchError> {
+
+			if let Some(metadata) = &metadata {
+				Self::validate_metadata(metadata)?;
+			}
+			if let Some(conditions) = &conditions {
+				ensure!(
+					conditions.min_temperature <= conditions.max_temperature && conditions.min_humidity <= conditions.max_humidity,
+					Error::<T>::InvalidConditionLimits);
+				// Breaches cost the holder no more than failing the shipment would
+				ensure!(conditions.penalty <= T::FailureSlash::get(), Error::<T>::PenaltyTooHigh);
+			}
+			if let RouteObjective::Weighted { cost, transit_time, emissions } = objective {
+				ensure!(cost != 0 || transit_time != 0 || emissions != 0, Error::<T>::InvalidObjective);
+			}
+			let shipment_uid = Self::shipment_uid().checked_add(1).ok_or(ArithmeticError::Overflow)?;
+
+			let route = Self::find_route(&transit_node, &destination, &objective)?;
+			let leg_fees = Self::quote_legs(&route)?;
+			let fees = leg_fees.iter().fold(BalanceOf::<T>::zero(), |total, fee| total.saturating_add(*fee));
+			let leg_deadlines = Self::plan_deadlines(leg_fees.len())?;
+			let metrics = Self::measure_route(&route);
+
+			T::Currency::reserve(&creator, fees)?;
+
+			let shipment = Shipment::<T> {
+				creator: creator.clone(),
+				fees: Some(fees),
+				leg_fees,
+				leg_deadlines,
+				owner_index: 1,
+				route,
+				destination: destination.clone(),
+				uid: shipment_uid.clone(),
+				status: ShipmentStatus::InTransit,
+				objective,
+				metrics,
+			};
+
+			ensure!(!UIDToShipment::<T>::contains_key(&shipment_uid), Error::<T>::ShipmentAlreadyExists);
+			PlannedArrivals::<T>::insert(&shipment_uid, Self::plan_arrivals(&shipment.route)?);
+			Self::schedule_deadline(&shipment);
+			UIDToShipment::<T>::insert(&shipment_uid, &shipment);
+
+			UIDToCommitment::<T>::insert(&shipment_uid, &commitment);
+			if let Some(metadata) = metadata {
+				UIDToMetadata::<T>::insert(&shipment_uid, metadata);
+			}
+			if let Some(conditions) = conditions {
+				UIDToConditions::<T>::insert(&shipment_uid, conditions);
+			}
+			ShipmentUID::<T>::put(shipment_uid);
+			ShipmentsByCreator::<T>::insert(&creator, shipment_uid, ());
+			ShipmentsByDestination::<T>::insert(&destination, shipment_uid, ());
+			Self::take_custody(&shipment);
+			Self::log_custody(&shipment, 0, &transit_node, None)?;
+
+			Self::deposit_event(Event::ShipmentFeesReserved(shipment_uid, creator, fees));
+			Self::deposit_event(Event::ShipmentCreated(transit_node));
+
+			Ok(shipment)
+		}
+
+		fn validate_metadata(metadata: &ShipmentMetadata<T>) -> DispatchResult {
+			ensure!(!metadata.content.is_empty(), Error::<T>::EmptyContent);
+			ensure!(metadata.mass <= T::MaxMass::get(), Error::<T>::MassTooHigh);
+			ensure!(metadata.volume <= T::MaxVolume::get(), Error::<T>::VolumeTooHigh);
+			ensure!(metadata.declared_value <= T::MaxDeclaredValue::get(), Error::<T>::DeclaredValueTooHigh);
+			ensure!(metadata.flags.0 & !HandlingFlags::ALL == 0, Error::<T>::UnknownHandlingFlags);
+			Ok(())
+		}
+
+		/// Commitment the holder stores on-chain for the secret revealed by `route[hop]`.
+		/// Binding the uid and hop stops a secret from being replayed on another handoff.
+		pub fn handoff_commitment(shipment_uid: u64, hop: u8, secret: &[u8; 32]) -> T::Hash {
+			T::Hashing::hash_of(&(shipment_uid, hop, secret))
+		}
+
+		/// Publishes the handoff secret, encrypted off-chain to the next holder's key.
+		fn seal_secret(shipment: &Shipment<T>, sealed_secret: BoundedVec<u8, T::MaxSealedSecretLen>) {
+			if sealed_secret.is_empty() {
+				return;
+			}
+			if let Some(next_holder) = shipment.route.get(shipment.owner_index as usize) {
+				Self::deposit_event(Event::HandoffSecretSealed(shipment.uid, next_holder.clone(), sealed_secret));
+			}
+		}
+
+		/// Records `holder` taking custody at `route[hop]`. The log only lives while the
+		/// shipment is in transit; the `CustodyTransferred` events keep the full history.
+		fn log_custody(shipment: &Shipment<T>, hop: u8, holder: &T::AccountId, note: Option<T::Hash>) -> DispatchResult {
+			let record = CustodyRecord::<T> {
+				hop,
+				holder: holder.clone(),
+				block: <frame_system::Pallet<T>>::block_number(),
+				moment: <pallet_timestamp::Pallet<T>>::get(),
+				note,
+			};
+			CustodyLog::<T>::try_append(&shipment.uid, record).map_err(|_| Error::<T>::TransitNodesOverFlow)?;
+			Self::deposit_event(Event::CustodyTransferred(shipment.uid, hop, holder.clone()));
+			Ok(())
+		}
+
+		/// Fee of every leg of `route` at the current edge costs.
+		fn quote_legs(route: &BoundedVec<T::AccountId, T::MaxSize>) -> Result<BoundedVec<BalanceOf<T>, T::MaxSize>, DispatchError> {
+			let mut leg_fees = BoundedVec::<BalanceOf<T>, T::MaxSize>::default();
+			for leg in route.windows(2) {
+				let cost = Self::edge(&leg[0], &leg[1]).ok_or(Error::<T>::InvalidRoute)?.cost;
+				let fee = T::PricePerCostUnit::get().saturating_mul(cost.into());
+				leg_fees.try_push(fee).map_err(|_| Error::<T>::TransitNodesOverFlow)?;
+			}
+			Ok(leg_fees)
+		}
+
+		/// Deadlines of `legs` consecutive legs starting from the current block.
+		fn plan_deadlines(legs: usize) -> Result<BoundedVec<T::BlockNumber, T::MaxSize>, DispatchError> {
+			let now = <frame_system::Pallet<T>>::block_number();
+			let mut deadlines = BoundedVec::<T::BlockNumber, T::MaxSize>::default();
+			for leg in 0..legs as u32 {
+				let deadline = now.saturating_add(T::LegTimeout::get().saturating_mul((leg + 1).into()));
+				deadlines.try_push(deadline).map_err(|_| Error::<T>::TransitNodesOverFlow)?;
+			}
+			Ok(deadlines)
+		}
+
+		/// Planned arrival at each hop of `route` after the first, leaving from the current block.
+		fn plan_arrivals(route: &[T::AccountId]) -> Result<BoundedVec<T::BlockNumber, T::MaxSize>, DispatchError> {
+			let mut arrival = <frame_system::Pallet<T>>::block_number();
+			let mut arrivals = BoundedVec::<T::BlockNumber, T::MaxSize>::default();
+			for leg in route.windows(2) {
+				arrival = arrival.saturating_add(Self::leg_duration(&leg[0], &leg[1]));
+				arrivals.try_push(arrival).map_err(|_| Error::<T>::TransitNodesOverFlow)?;
+			}
+			Ok(arrivals)
+		}
+
+		/// Expected transit duration of the edge from `from` to `to`.
+		fn leg_duration(from: &T::AccountId, to: &T::AccountId) -> T::BlockNumber {
+			Self::edge(from, to).map_or_else(T::LegTimeout::get, |edge| Self::transit_time_of(&edge))
+		}
+
+		/// Expected transit duration of `edge`.
+		fn transit_time_of(edge: &EdgeAttributes<T>) -> T::BlockNumber {
+			edge.transit_time.unwrap_or_else(T::LegTimeout::get)
+		}
+
+		/// Registers the deadline and the planned arrival of the shipment's current leg with
+		/// `on_initialize`.
+		fn schedule_deadline(shipment: &Shipment<T>) {
+			let leg = shipment.owner_index.saturating_sub(1) as usize;
+			if let Some(deadline) = shipment.leg_deadlines.get(leg) {
+				// Blocks before the cursor are not looked at again
+				Deadlines::<T>::insert((*deadline).max(Self::deadline_cursor()), shipment.uid, ());
+			}
+			if let Some(arrival) = Self::planned_arrivals(shipment.uid).get(leg) {
+				ArrivalChecks::<T>::insert((*arrival).max(Self::arrival_cursor()), shipment.uid, *arrival);
+			}
+		}
+
+		/// Expected arrival at the destination of an in-transit shipment: custody of the current
+		/// hop was taken at its last custody record, and every remaining leg takes its expected
+		/// duration. A leg running over is expected to complete no earlier than now.
+		pub fn current_eta(shipment_uid: u64) -> Option<T::BlockNumber> {
+			let shipment = Self::uid_to_shipment(shipment_uid)?;
+			if shipment.status != ShipmentStatus::InTransit {
+				return None;
+			}
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			let current = shipment.owner_index.saturating_sub(1) as usize;
+			let mut eta = Self::custody_log(shipment_uid).last().map_or(now, |record| record.block);
+			for (leg, hop) in shipment.route.windows(2).enumerate().skip(current) {
+				eta = eta.saturating_add(Self::leg_duration(&hop[0], &hop[1]));
+				if leg == current {
+					eta = eta.max(now);
+				}
+			}
+			Some(eta)
+		}
+
+		/// Releases the reserved fee of leg `leg` from the creator to `transit_node`.
+		fn pay_leg(shipment: &Shipment<T>, leg: usize, transit_node: &T::AccountId) -> DispatchResult {
+			let fee = shipment.leg_fees.get(leg).copied().unwrap_or_else(Zero::zero);
+			if fee.is_zero() {
+				return Ok(());
+			}
+			T::Currency::repatriate_reserved(&shipment.creator, transit_node, fee, BalanceStatus::Free)?;
+			Self::deposit_event(Event::ShipmentFeePaid(shipment.uid, transit_node.clone(), fee));
+			Ok(())
+		}
+
+		/// Marks an in-transit shipment as `Failed` and returns the fees of the legs that
+		/// have not been completed to its creator. Returns the number of shipments failed,
+		/// including those packed into it.
+		pub fn fail_shipment(shipment: &mut Shipment<T>) -> u32 {
+			if shipment.status != ShipmentStatus::InTransit {
+				return 0;
+			}
+			Self::release_custody(shipment);
+
+			let completed = (shipment.owner_index as usize).saturating_sub(1);
+			let remaining = shipment.leg_fees.iter().skip(completed)
+				.fold(BalanceOf::<T>::zero(), |total, fee| total.saturating_add(*fee));
+			let not_refunded = T::Currency::unreserve(&shipment.creator, remaining);
+
+			shipment.status = ShipmentStatus::Failed;
+			UIDToShipment::<T>::insert(&shipment.uid, &*shipment);
+			Self::queue_archival(shipment.uid);
+			UIDToCommitment::<T>::remove(&shipment.uid);
+			CustodyLog::<T>::remove(&shipment.uid);
+
+			Self::deposit_event(Event::ShipmentFeesRefunded(shipment.uid, shipment.creator.clone(), remaining.saturating_sub(not_refunded)));
+
+			if let Some(parent_uid) = ParentOf::<T>::take(&shipment.uid) {
+				Children::<T>::mutate(&parent_uid, |packed| packed.retain(|uid| *uid != shipment.uid));
+			}
+			// Packed shipments are lost with their parent. They are failed while still packed, as
+			// their custody was already released when packing them.
+			let mut failed: u32 = 1;
+			for child_uid in Self::children(&shipment.uid).into_inner() {
+				if let Some(mut child) = Self::uid_to_shipment(child_uid) {
+					failed = failed.saturating_add(Self::fail_shipment(&mut child));
+				}
+				ParentOf::<T>::remove(&child_uid);
+			}
+			Children::<T>::remove(&shipment.uid);
+			failed
+		}
+
+		/// Completes a shipment unpacked at its destination. The parent carried it there,
+		/// so the fees of its own remaining legs go back to the creator.
+		fn deliver_unpacked(shipment: &mut Shipment<T>, destination: &T::AccountId) -> DispatchResult {
+			let completed = (shipment.owner_index as usize).saturating_sub(1);
+			let remaining = shipment.leg_fees.iter().skip(completed)
+				.fold(BalanceOf::<T>::zero(), |total, fee| total.saturating_add(*fee));
+			let not_refunded = T::Currency::unreserve(&shipment.creator, remaining);
+			Self::log_custody(shipment, completed as u8, destination, None)?;
+
+			shipment.owner_index = 0;
+			shipment.status = ShipmentStatus::AwaitingAcceptance;
+			UIDToShipment::<T>::insert(&shipment.uid, &*shipment);
+			UIDToCommitment::<T>::remove(&shipment.uid);
+			CustodyLog::<T>::remove(&shipment.uid);
+
+			Self::deposit_event(Event::ShipmentFeesRefunded(shipment.uid, shipment.creator.clone(), remaining.saturating_sub(not_refunded)));
+			Self::deposit_event(Event::ShipmentReceived(destination.clone()));
+			Ok(())
+		}
+
+		/// Queues a finalized shipment for archival once `RetentionPeriod` has passed.
+		pub(crate) fn queue_archival(shipment_uid: u64) {
+			let tail = Self::archival_tail();
+			ArchivalQueue::<T>::insert(tail, (shipment_uid, <frame_system::Pallet<T>>::block_number()));
+			ArchivalTail::<T>::put(tail.saturating_add(1));
+		}
+
+		/// Hash kept on-chain for an archived shipment, committing to the records it leaves
+		/// behind so that they can be proven from an archive node or an offchain index.
+		pub fn archive_hash(shipment: &Shipment<T>, records: &ShipmentRecords<T>) -> T::Hash {
+			T::Hashing::hash_of(&(shipment, records))
+		}
+
+		/// Records of a shipment that archiving it removes besides the shipment itself.
+		pub fn shipment_records(shipment_uid: u64) -> ShipmentRecords<T> {
+			ShipmentRecords {
+				metadata: Self::shipment_metadata(shipment_uid),
+				conditions: Self::shipment_conditions(shipment_uid),
+				breaches: Self::breaches(shipment_uid),
+				rejection: Self::rejections(shipment_uid),
+				planned_arrivals: Self::planned_arrivals(shipment_uid),
+				latest_telemetry: Self::latest_telemetry(shipment_uid),
+				leg_emissions: Self::leg_emissions(shipment_uid),
+				emissions: Self::shipment_emissions(shipment_uid),
+				return_of: Self::return_of(shipment_uid),
+			}
+		}
+
+		/// Replaces every record of a finalized shipment with its `archive_hash`. Parents
+		/// still carrying packed shipments wait for another retention period.
+		fn archive_shipment(shipment_uid: u64) {
+			let shipment = match Self::uid_to_shipment(shipment_uid) {
+				Some(shipment) => shipment,
+				None => return,
+			};
+			if !Self::children(shipment_uid).is_empty() {
+				Self::queue_archival(shipment_uid);
+				return;
+			}
+
+			let records = Self::shipment_records(shipment_uid);
+			let record_hash = Self::archive_hash(&shipment, &records);
+
+			// Completed legs keep their hops when rerouted, so each one's carrier is still on the route
+			for (leg, _, block) in records.leg_emissions.iter() {
+				EmissionsLedger::<T>::remove((&shipment.creator, block, shipment_uid));
+				if let Some(carrier) = shipment.route.get(*leg as usize) {
+					EmissionsLedger::<T>::remove((carrier, block, shipment_uid));
+				}
+			}
+
+			UIDToShipment::<T>::remove(shipment_uid);
+			UIDToMetadata::<T>::remove(shipment_uid);
+			UIDToConditions::<T>::remove(shipment_uid);
+			Breaches::<T>::remove(shipment_uid);
+			Rejections::<T>::remove(shipment_uid);
+			UIDToCommitment::<T>::remove(shipment_uid);
+			CustodyLog::<T>::remove(shipment_uid);
+			PlannedArrivals::<T>::remove(shipment_uid);
+			LatestTelemetry::<T>::remove(shipment_uid);
+			NextTelemetryAt::<T>::remove(shipment_uid);
+			LegEmissions::<T>::remove(shipment_uid);
+			ShipmentEmissions::<T>::remove(shipment_uid);
+			ReturnOf::<T>::remove(shipment_uid);
+			Disputes::<T>::remove(shipment_uid);
+			ShipmentsByCreator::<T>::remove(&shipment.creator, shipment_uid);
+			ShipmentsByDestination::<T>::remove(&shipment.destination, shipment_uid);
+			ArchivedShipments::<T>::insert(shipment_uid, record_hash);
+
+			Self::deposit_event(Event::ShipmentArchived(shipment_uid, record_hash));
+		}
+
+		/// Compensates the creator of a shipment with up to `amount` from the bond of the
+		/// node holding it. Failures caused by changes to the network are only slashed when
+		/// made by the holder's own operator.
+		fn slash_holder(shipment: &Shipment<T>, amount: BalanceOf<T>) {
+			let holder = match Self::holder_of(shipment) {
+				Some(holder) if holder != shipment.creator => holder,
+				_ => return,
+			};
+			let bond = Self::bonds(&holder).unwrap_or_else(Zero::zero);
+			let slash = bond.min(amount);
+			if slash.is_zero() {
+				return;
+			}
+
+			let not_slashed = T::Currency::repatriate_reserved(&Self::bonder_of(&holder), &shipment.creator, slash, BalanceStatus::Free)
+				.unwrap_or(slash);
+			let slashed = slash.saturating_sub(not_slashed);
+			Bonds::<T>::insert(&holder, bond.saturating_sub(slashed));
+
+			Self::deposit_event(Event::TransitNodeSlashed(holder, shipment.uid, slashed));
+		}
+
+		/// Ensures that `from` can have an edge to `to` without exceeding `MaxEdgesPerNode`.
+		fn ensure_edge_room(from: &T::AccountId, to: &T::AccountId) -> DispatchResult {
+			if Edges::<T>::contains_key(from, to) {
+				return Ok(());
+			}
+			let max_edges = T::MaxEdgesPerNode::get() as usize;
+			ensure!(Edges::<T>::iter_key_prefix(from).take(max_edges).count() < max_edges, Error::<T>::TooManyEdges);
+			Ok(())
+		}
+
+		/// Sets the cost of the edge from `from` to `to`, keeping its other attributes.
+		fn set_edge_cost(from: &T::AccountId, to: &T::AccountId, cost: u32) {
+			Edges::<T>::mutate(from, to, |edge| match edge {
+				Some(edge) => edge.cost = cost,
+				None => *edge = Some(EdgeAttributes::with_cost(cost)),
+			});
+		}
+
+		/// Applies `update` to the edge from `node1` to `node2` and, unless the lane is
+		/// one-way, to the opposite edge.
+		fn update_lane(node1: &T::AccountId, node2: &T::AccountId, update: impl Fn(&mut EdgeAttributes<T>)) {
+			for (from, to) in [(node1, node2), (node2, node1)] {
+				Edges::<T>::mutate(from, to, |edge| if let Some(edge) = edge {
+					update(edge);
+				});
+			}
+		}
+
+		/// Account the bond of `transit_node` is reserved from.
+		pub fn bonder_of(transit_node: &T::AccountId) -> T::AccountId {
+			Self::bonded_by(transit_node).unwrap_or_else(|| transit_node.clone())
+		}
+
+		/// Any change to the transit network may make a cached route stale or suboptimal.
+		/// Starting a new generation invalidates every cached route without touching them.
+		fn invalidate_routes() {
+			RouteGeneration::<T>::mutate(|generation| *generation = generation.wrapping_add(1));
+		}
+
+		/// Cheapest route of the lane from `origin` to `dest`, if planned since the last
+		/// change to the transit network.
+		pub fn cached_route(origin: &T::AccountId, dest: &T::AccountId) -> Option<BoundedVec<T::AccountId, T::MaxSize>> {
+			Self::route_vec(origin, dest)
+				.filter(|(generation, _)| *generation == Self::route_generation())
+				.map(|(_, route)| route)
+		}
+
+		/// Weight of the longest route search `MaxRouteSearchIterations` allows.
+		fn max_route_search_weight() -> Weight {
+			T::WeightInfo::route_search(T::MaxRouteSearchIterations::get())
+		}
+
+		/// Weight of the route searches of a reroute, which searches again through saturated
+		/// nodes when no route avoids them.
+		fn reroute_search_weight() -> Weight {
+			Self::max_route_search_weight().saturating_mul(2)
+		}
+
+		/// Weight of rerouting a shipment.
+		fn reroute_weight() -> Weight {
+			T::WeightInfo::reroute_shipment().saturating_add(Self::reroute_search_weight())
+		}
+
+		/// Weight of rerouting as many shipments as a change to the transit network reroutes
+		/// right away.
+		fn max_reroute_weight() -> Weight {
+			Self::reroute_weight().saturating_mul(T::MaxReroutesPerBlock::get() as Weight)
+		}
+
+		/// Queues the in-transit shipments affected by a change `operator` made for rerouting,
+		/// and reroutes as many queued shipments as a block allows right away.
+		fn queue_reroutes(change: NetworkChange<T::AccountId>, operator: Option<T::AccountId>) {
+			let tail = Self::reroute_tail();
+			RerouteQueue::<T>::insert(tail, (change, operator));
+			RerouteTail::<T>::put(tail.saturating_add(1));
+			Self::process_reroutes(T::MaxReroutesPerBlock::get());
+		}
+
+		/// Looks at up to `limit` shipments listed under the node of the oldest queued changes,
+		/// rerouting those affected. Each change counts as at least one shipment. Returns how
+		/// many of `limit` were used.
+		pub(crate) fn process_reroutes(limit: u32) -> u32 {
+			let mut remaining = limit;
+			let mut head = Self::reroute_head();
+			while head < Self::reroute_tail() && remaining > 0 {
+				let (change, operator) = match RerouteQueue::<T>::get(head) {
+					Some(job) => job,
+					None => {
+						head = head.saturating_add(1);
+						continue;
+					},
+				};
+				let node = change.node();
+				// A rerouted shipment keeps its position under the node, so it is not looked at twice
+				let uids: Vec<u64> = match Self::reroute_cursor() {
+					Some(last) => ShipmentsByRouteNode::<T>::iter_key_prefix_from(
+						node,
+						ShipmentsByRouteNode::<T>::hashed_key_for(node, last)
+					),
+					None => ShipmentsByRouteNode::<T>::iter_key_prefix(node),
+				}
+					.take(remaining as usize)
+					.collect();
+
+				let finished = uids.len() < remaining as usize;
+				remaining = remaining.saturating_sub((uids.len() as u32).max(1));
+				match uids.last() {
+					Some(last) if !finished => RerouteCursor::<T>::put(last),
+					_ => {
+						RerouteQueue::<T>::remove(head);
+						RerouteCursor::<T>::kill();
+						head = head.saturating_add(1);
+					},
+				}
+
+				for uid in uids {
+					Self::reroute_if_affected(uid, &change, &operator);
+				}
+			}
+			RerouteHead::<T>::put(head);
+
+			limit.saturating_sub(remaining)
+		}
+
+		/// Re-plans the remaining legs of an in-transit shipment if `change` affects them.
+		/// Shipments that can no longer reach their destination are failed, at the expense of
+		/// the holder if its own `operator` made the change.
+		fn reroute_if_affected(shipment_uid: u64, change: &NetworkChange<T::AccountId>, operator: &Option<T::AccountId>) {
+			let mut shipment = match Self::uid_to_shipment(shipment_uid) {
+				Some(shipment) if shipment.status == ShipmentStatus::InTransit => shipment,
+				_ => return,
+			};
+			// Packed shipments are re-planned when unpacked
+			if ParentOf::<T>::contains_key(&shipment_uid) {
+				return;
+			}
+			let completed = (shipment.owner_index as usize).saturating_sub(1);
+			if !shipment.route.get(completed..).map_or(false, |remaining| change.affects(remaining)) {
+				return;
+			}
+
+			let holder = match shipment.current_holder() {
+				Some(holder) => holder.clone(),
+				None => return,
+			};
+			if Self::reroute(&mut shipment, &holder).is_err() {
+				if operator.is_some() && Self::node_operator(&holder) == *operator {
+					Self::slash_holder(&shipment, T::FailureSlash::get());
+				}
+				Self::fail_shipment(&mut shipment);
+			}
+		}
+
+		/// Replaces the route from the current hop to the destination with the best one from
+		/// `holder` for the shipment's objective, re-quoting its fees and giving each remaining leg a fresh deadline.
+		/// The pending handoff commitment is bound to the hop rather than the account, so it
+		/// stays valid while `holder` is unchanged. Nothing is reserved or stored if any step fails.
+		#[transactional]
+		fn reroute(shipment: &mut Shipment<T>, holder: &T::AccountId) -> DispatchResult {
+			let completed = (shipment.owner_index as usize).saturating_sub(1);
+			ensure!(completed < shipment.route.len(), Error::<T>::InvalidRoute);
+
+			// Shipments already under way rather pass through saturated nodes than fail
+			let objective = shipment.objective;
+			let remaining = Self::best_route(holder, &shipment.destination, &objective, true)
+				.or_else(|_| Self::best_route(holder, &shipment.destination, &objective, false))?;
+			let remaining_fees = Self::quote_legs(&remaining)?;
+			let remaining_deadlines = Self::plan_deadlines(remaining_fees.len())?;
+			let remaining_arrivals = Self::plan_arrivals(&remaining)?;
+
+			// Legs migrated from before fees and deadlines existed may be missing entries
+			let now = <frame_system::Pallet<T>>::block_number();
+			let mut route = shipment.route[..completed].to_vec();
+			route.extend(remaining.into_inner());
+			let mut leg_fees: Vec<BalanceOf<T>> = (0..completed)
+				.map(|leg| shipment.leg_fees.get(leg).copied().unwrap_or_else(Zero::zero))
+				.collect();
+			leg_fees.extend(remaining_fees.iter().copied());
+			let mut leg_deadlines: Vec<T::BlockNumber> = (0..completed)
+				.map(|leg| shipment.leg_deadlines.get(leg).copied().unwrap_or(now))
+				.collect();
+			leg_deadlines.extend(remaining_deadlines.into_inner());
+
+			let route: BoundedVec<T::AccountId, T::MaxSize> = route.try_into().map_err(|_| Error::<T>::TransitNodesOverFlow)?;
+			let leg_fees: BoundedVec<BalanceOf<T>, T::MaxSize> = leg_fees.try_into().map_err(|_| Error::<T>::TransitNodesOverFlow)?;
+			let leg_deadlines: BoundedVec<T::BlockNumber, T::MaxSize> = leg_deadlines.try_into().map_err(|_| Error::<T>::TransitNodesOverFlow)?;
+			let mut arrivals = Self::planned_arrivals(shipment.uid).into_inner();
+			arrivals.resize(completed, now);
+			arrivals.extend(remaining_arrivals.into_inner());
+			let arrivals: BoundedVec<T::BlockNumber, T::MaxSize> = arrivals.try_into().map_err(|_| Error::<T>::TransitNodesOverFlow)?;
+
+			let old_remaining = shipment.leg_fees.iter().skip(completed)
+				.fold(BalanceOf::<T>::zero(), |total, fee| total.saturating_add(*fee));
+			let new_remaining = remaining_fees.iter()
+				.fold(BalanceOf::<T>::zero(), |total, fee| total.saturating_add(*fee));
+			if new_remaining > old_remaining {
+				let extra = new_remaining.saturating_sub(old_remaining);
+				T::Currency::reserve(&shipment.creator, extra)?;
+				Self::deposit_event(Event::ShipmentFeesReserved(shipment.uid, shipment.creator.clone(), extra));
+			} else if new_remaining < old_remaining {
+				let refund = old_remaining.saturating_sub(new_remaining);
+				let not_refunded = T::Currency::unreserve(&shipment.creator, refund);
+				Self::deposit_event(Event::ShipmentFeesRefunded(shipment.uid, shipment.creator.clone(), refund.saturating_sub(not_refunded)));
+			}
+
+			// Only replace the caller's copy once every step succeeded, as storage is rolled back otherwise
+			let mut rerouted = shipment.clone();
+			Self::release_custody(&rerouted);
+			let old_route = sp_std::mem::replace(&mut rerouted.route, route);
+			rerouted.fees = Some(leg_fees.iter().fold(BalanceOf::<T>::zero(), |total, fee| total.saturating_add(*fee)));
+			rerouted.leg_fees = leg_fees;
+			rerouted.leg_deadlines = leg_deadlines;
+			rerouted.metrics = Self::measure_route(&rerouted.route);
+			PlannedArrivals::<T>::insert(&rerouted.uid, arrivals);
+			Self::take_custody(&rerouted);
+			Self::schedule_deadline(&rerouted);
+			UIDToShipment::<T>::insert(&rerouted.uid, &rerouted);
+
+			if old_route != rerouted.route {
+				Self::deposit_event(Event::ShipmentRerouted(rerouted.uid, old_route, rerouted.route.clone()));
+			}
+			*shipment = rerouted;
+
+			Ok(())
+		}
+
+		/// Best route from `origin` to `dest` for `objective`. Cheapest routes are served from
+		/// `RouteVector` when the lane has already been planned since the last change to the
+		/// transit network.
+		fn find_route(
+			origin: &T::AccountId,
+			dest: &T::AccountId,
+			objective: &RouteObjective
+		) -> Result<BoundedVec<T::AccountId, T::MaxSize>, Error<T>> {
+
+			if *objective != RouteObjective::Cheapest {
+				return Self::best_route(origin, dest, objective, true);
+			}
+			if let Some(route) = Self::cached_route(origin, dest) {
+				if !Self::crosses_saturated(&route) {
+					return Ok(route);
+				}
+			}
+
+			let route = Self::best_route(origin, dest, objective, true)?;
+			RouteVector::<T>::insert(origin, dest, (Self::route_generation(), &route));
+
+			Ok(route)
+		}
+
+		/// What `objective` minimises on `edge`.
+		fn edge_weight(objective: &RouteObjective, edge: &EdgeAttributes<T>) -> u64 {
+			let cost = edge.cost;
+			let transit_time = || -> u64 { Self::transit_time_of(edge).unique_saturated_into() };
+			let emissions = || Self::emissions_of(edge);
+			match *objective {
+				RouteObjective::Cheapest => cost as u64,
+				RouteObjective::Fastest => transit_time(),
+				RouteObjective::Greenest => emissions(),
+				RouteObjective::Weighted { cost: cost_weight, transit_time: time_weight, emissions: emissions_weight } =>
+					(cost as u64).saturating_mul(cost_weight as u64)
+						.saturating_add(transit_time().saturating_mul(time_weight as u64))
+						.saturating_add(emissions().saturating_mul(emissions_weight as u64)),
+			}
+		}
+
+		/// Grams of CO₂ equivalent estimated for carrying a shipment from `from` to `to`.
+		pub fn edge_emissions(from: &T::AccountId, to: &T::AccountId) -> u64 {
+			Self::edge(from, to).map_or(0, |edge| Self::emissions_of(&edge))
+		}
+
+		/// Grams of CO₂ equivalent estimated for carrying a shipment over `edge`. Edges without
+		/// a profile, or whose mode has no emission factor, are emission-free.
+		fn emissions_of(edge: &EdgeAttributes<T>) -> u64 {
+			edge.profile.map_or(0, |profile| {
+				(profile.distance as u64).saturating_mul(Self::emission_factor(profile.mode) as u64)
+			})
+		}
+
+		/// Charges leg `leg` of a shipment its estimated emissions, accounted to the shipment,
+		/// its creator and the node that carried it.
+		fn record_leg_emissions(shipment: &Shipment<T>, leg: usize) -> DispatchResult {
+			let (carrier, next_hop) = match (shipment.route.get(leg), shipment.route.get(leg + 1)) {
+				(Some(carrier), Some(next_hop)) => (carrier, next_hop),
+				_ => return Ok(()),
+			};
+			let emissions = Self::edge_emissions(carrier, next_hop);
+			let now = <frame_system::Pallet<T>>::block_number();
+
+			LegEmissions::<T>::try_mutate(shipment.uid, |legs| legs.try_push((leg as u8, emissions, now)))
+				.map_err(|_| Error::<T>::TooManyLegEmissions)?;
+			ShipmentEmissions::<T>::mutate(shipment.uid, |total| *total = total.saturating_add(emissions));
+			NodeEmissions::<T>::mutate(carrier, |total| *total = total.saturating_add(emissions));
+			EmissionsLedger::<T>::mutate((&shipment.creator, now, shipment.uid), |(created, _)| {
+				*created = created.saturating_add(emissions)
+			});
+			EmissionsLedger::<T>::mutate((carrier, now, shipment.uid), |(_, carried)| {
+				*carried = carried.saturating_add(emissions)
+			});
+
+			Self::deposit_event(Event::LegEmissionsRecorded(shipment.uid, leg as u8, carrier.clone(), emissions));
+			Ok(())
+		}
+
+		/// Emissions `account` is accountable for from legs completed between blocks `from` and
+		/// `to` inclusive: as creator, as carrier and per shipment, counting legs it both created
+		/// and carried once. Only the ledger entries of blocks in the range are read, and at
+		/// most `MAX_REPORT_BLOCKS` of them, so the last block covered is returned as well.
+		/// Legs of archived shipments are left out, their entries being removed with them.
+		pub fn emissions_report(
+			account: &T::AccountId,
+			from: T::BlockNumber,
+			to: T::BlockNumber
+		) -> (u64, u64, Vec<(u64, u64)>, T::BlockNumber) {
+			let mut as_creator: u64 = 0;
+			let mut as_carrier: u64 = 0;
+			let mut per_shipment: BTreeMap<u64, u64> = BTreeMap::new();
+			// No leg completes after the current block
+			let to = to
+				.min(<frame_system::Pallet<T>>::block_number())
+				.min(from.saturating_add(MAX_REPORT_BLOCKS.saturating_sub(1).into()));
+			let mut block = from;
+			while block <= to {
+				for (uid, (created, carried)) in EmissionsLedger::<T>::iter_prefix((account, block)) {
+					as_creator = as_creator.saturating_add(created);
+					as_carrier = as_carrier.saturating_add(carried);
+					// The legs carried are among those created when the account is both
+					let total = per_shipment.entry(uid).or_insert(0);
+					*total = total.saturating_add(created.max(carried));
+				}
+				if block == to {
+					break;
+				}
+				block = block.saturating_add(One::one());
+			}
+			(as_creator, as_carrier, per_shipment.into_iter().collect(), to)
+		}
+
+		/// Cost, transit time and emissions summed over the legs of `route`.
+		pub fn measure_route(route: &[T::AccountId]) -> RouteMetrics<T> {
+			route.windows(2).fold(
+				RouteMetrics { cost: 0, transit_time: Zero::zero(), emissions: 0 },
+				|metrics, leg| match Self::edge(&leg[0], &leg[1]) {
+					Some(edge) => RouteMetrics {
+						cost: metrics.cost.saturating_add(edge.cost as u64),
+						transit_time: metrics.transit_time.saturating_add(Self::transit_time_of(&edge)),
+						emissions: metrics.emissions.saturating_add(Self::emissions_of(&edge)),
+					},
+					// Legs over edges removed since count as taking `LegTimeout` for free
+					None => RouteMetrics {
+						transit_time: metrics.transit_time.saturating_add(T::LegTimeout::get()),
+						..metrics
+					},
+				},
+			)
+		}
+
+		/// Dijkstra over the `Edges` graph, weighing edges by `objective`. Labels are kept
+		/// per (node, hops) so that the best route is found among those visiting at most
+		/// `MaxSize` nodes. With `avoid_saturated`, saturated nodes are only used as the
+		/// destination.
+		fn best_route(
+			origin: &T::AccountId,
+			dest: &T::AccountId,
+			objective: &RouteObjective,
+			avoid_saturated: bool
+		) -> Result<BoundedVec<T::AccountId, T::MaxSize>, Error<T>> {
+
+			ensure!(origin != dest, Error::<T>::InvalidRoute);
+
+			let max_nodes = T::MaxSize::get();
+			let max_iterations = T::MaxRouteSearchIterations::get();
+			// Edges beyond the bound can only predate it
+			let max_edges = T::MaxEdgesPerNode::get() as usize;
+
+			// (weight, hops, node) ordered so that the lightest label is always first
+			let mut queue: BTreeSet<(u64, u32, T::AccountId)> = BTreeSet::new();
+			let mut best: BTreeMap<(T::AccountId, u32), u64> = BTreeMap::new();
+			let mut previous: BTreeMap<(T::AccountId, u32), T::AccountId> = BTreeMap::new();
+			// Fewest hops with which a node has been settled
+			let mut settled: BTreeMap<T::AccountId, u32> = BTreeMap::new();
+			let mut iterations: u32 = 0;
+
+			queue.insert((0, 0, origin.clone()));
+			best.insert((origin.clone(), 0), 0);
+
+			while let Some(label) = queue.iter().next().cloned() {
+				queue.remove(&label);
+				let (cost, hops, node) = label;
+
+				iterations = iterations.saturating_add(1);
+				ensure!(iterations <= max_iterations, Error::<T>::RouteSearchLimitReached);
+
+				// A label settled earlier is cheaper and used no more hops
+				if settled.get(&node).map_or(false, |settled_hops| *settled_hops <= hops) {
+					continue;
+				}
+				settled.insert(node.clone(), hops);
+
+				if &node == dest {
+					let mut route = vec![node.clone()];
+					let mut current = (node, hops);
+					while current.1 > 0 {
+						let prev = previous.get(&current).cloned().ok_or(Error::<T>::InvalidRoute)?;
+						route.push(prev.clone());
+						current = (prev, current.1 - 1);
+					}
+					route.reverse();
+					return route.try_into().map_err(|_| Error::<T>::TransitNodesOverFlow);
+				}
+
+				// Route would no longer fit in `MaxSize` nodes
+				if hops.saturating_add(2) > max_nodes {
+					continue;
+				}
+
+				for (neighbour, edge) in Edges::<T>::iter_prefix(&node).take(max_edges) {
+					let next_hops = hops + 1;
+					if settled.get(&neighbour).map_or(false, |settled_hops| *settled_hops <= next_hops) {
+						continue;
+					}
+					if avoid_saturated && &neighbour != dest && Self::is_saturated(&neighbour) {
+						continue;
+					}
+					let next_cost = cost.saturating_add(Self::edge_weight(objective, &edge));
+					let key = (neighbour.clone(), next_hops);
+					if best.get(&key).map_or(true, |known| next_cost < *known) {
+						best.insert(key.clone(), next_cost);
+						previous.insert(key, node.clone());
+						queue.insert((next_cost, next_hops, neighbour));
+					}
+				}
+			}
+
+			// Origin and destination are not connected
+			Err(Error::<T>::InvalidRoute)
+		}
+
+		/// Whether `transit_node` holds or expects as many shipments as its capacity allows.
+		pub fn is_saturated(transit_node: &T::AccountId) -> bool {
+			Self::capacity(transit_node).map_or(false, |capacity| {
+				Self::custody_count(transit_node).saturating_add(Self::inbound_count(transit_node)) >= capacity
+			})
+		}
+
+		/// Whether a node between the ends of `route` is saturated.
+		fn crosses_saturated(route: &[T::AccountId]) -> bool {
+			route.len() > 2 && route[1..route.len() - 1].iter().any(Self::is_saturated)
+		}
+
+		/// Counts an in-transit shipment against the capacity of its holder, indexes it under
+		/// the holder and queues it at its next hop. Packed shipments travel inside their
+		/// parent and are not counted.
+		pub(crate) fn take_custody(shipment: &Shipment<T>) {
+			if shipment.status != ShipmentStatus::InTransit || ParentOf::<T>::contains_key(&shipment.uid) {
+				return;
+			}
+			if let Some(holder) = shipment.current_holder() {
+				CustodyCount::<T>::mutate(holder, |count| *count = count.saturating_add(1));
+				ShipmentsByHolder::<T>::insert(holder, shipment.uid, ());
+			}
+			let completed = (shipment.owner_index as usize).saturating_sub(1);
+			for node in shipment.route.iter().skip(completed) {
+				ShipmentsByRouteNode::<T>::insert(node, shipment.uid, ());
+			}
+			if let Some(next_hop) = shipment.route.get(shipment.owner_index as usize) {
+				let leg = shipment.owner_index.saturating_sub(1) as usize;
+				let arrival = Self::planned_arrivals(shipment.uid).get(leg).copied()
+					.unwrap_or_else(<frame_system::Pallet<T>>::block_number);
+				if !InboundQueue::<T>::contains_key(next_hop, shipment.uid) {
+					InboundCount::<T>::mutate(next_hop, |count| *count = count.saturating_add(1));
+				}
+				InboundQueue::<T>::insert(next_hop, shipment.uid, arrival);
+			}
+		}
+
+		/// Undoes `take_custody` before the holder or next hop of a shipment changes.
+		fn release_custody(shipment: &Shipment<T>) {
+			if shipment.status != ShipmentStatus::InTransit || ParentOf::<T>::contains_key(&shipment.uid) {
+				return;
+			}
+			if let Some(holder) = shipment.current_holder() {
+				CustodyCount::<T>::mutate(holder, |count| *count = count.saturating_sub(1));
+				ShipmentsByHolder::<T>::remove(holder, shipment.uid);
+			}
+			let completed = (shipment.owner_index as usize).saturating_sub(1);
+			for node in shipment.route.iter().skip(completed) {
+				ShipmentsByRouteNode::<T>::remove(node, shipment.uid);
+			}
+			if let Some(next_hop) = shipment.route.get(shipment.owner_index as usize) {
+				if InboundQueue::<T>::take(next_hop, shipment.uid).is_some() {
+					InboundCount::<T>::mutate(next_hop, |count| *count = count.saturating_sub(1));
+				}
+			}
+		}
+
+		/// Shipments on their way to `transit_node`, earliest planned arrival first.
+		pub fn inbound_shipments(transit_node: &T::AccountId) -> Vec<(u64, T::BlockNumber)> {
+			let mut inbound: Vec<(u64, T::BlockNumber)> = InboundQueue::<T>::iter_prefix(transit_node).collect();
+			inbound.sort_by_key(|(uid, arrival)| (*arrival, *uid));
+			inbound
+		}
+
+		/// Uids of the in-transit shipments currently held by `account`, including those packed
+		/// in a parent it holds or that has arrived at it.
+		pub fn shipments_held_by(account: &T::AccountId) -> Vec<u64> {
+			let arrived = ShipmentsByDestination::<T>::iter_key_prefix(account)
+				.filter(|uid| Self::uid_to_shipment(uid).map_or(false, |shipment| shipment.has_arrived()));
+			let mut held: Vec<u64> = ShipmentsByHolder::<T>::iter_key_prefix(account).collect();
+			let packed: Vec<u64> = held.iter().copied()
+				.chain(arrived)
+				.flat_map(|parent_uid| Self::children(parent_uid).into_inner())
+				.filter(|uid| Self::uid_to_shipment(uid).and_then(|child| Self::holder_of(&child)).as_ref() == Some(account))
+				.collect();
+			held.extend(packed);
+			held.sort();
+			held
+		}
+
+		/// Up to `limit` uids of the shipments `account` relates to as `role`, capped at
+		/// `MAX_SHIPMENTS_PAGE`. Pages follow the storage order of the index, so passing the
+		/// last uid of a page as `start_after` returns the next one.
+		pub fn shipments_by(role: ShipmentRole, account: &T::AccountId, start_after: Option<u64>, limit: u32) -> Vec<u64> {
+			let limit = limit.min(MAX_SHIPMENTS_PAGE) as usize;
+			macro_rules! page {
+				($index:ident) => {
+					match start_after {
+						Some(uid) => $index::<T>::iter_key_prefix_from(account, $index::<T>::hashed_key_for(account, uid))
+							.take(limit)
+							.collect(),
+						None => $index::<T>::iter_key_prefix(account).take(limit).collect(),
+					}
+				};
+			}
+			match role {
+				ShipmentRole::Creator => page!(ShipmentsByCreator),
+				ShipmentRole::Destination => page!(ShipmentsByDestination),
+				ShipmentRole::Holder => page!(ShipmentsByHolder),
+			}
+		}
+
+		/// Current holder of an in-transit shipment. Packed shipments are held by whoever
+		/// holds their parent, or by its destination once the parent has arrived.
+		pub fn holder_of(shipment: &Shipment<T>) -> Option<T::AccountId> {
+			if shipment.status != ShipmentStatus::InTransit {
+				return None;
+			}
+			match Self::parent_of(&shipment.uid).and_then(Self::uid_to_shipment) {
+				Some(parent) if parent.has_arrived() => Some(parent.destination),
+				Some(parent) => parent.current_holder().cloned(),
+				None => shipment.current_holder().cloned(),
+			}
+		}
+
+		/// Route, its metrics and the fees `create_shipment` would charge for `objective`,
+		/// without touching the route cache.
+		pub fn quote_route(
+			origin: &T::AccountId,
+			dest: &T::AccountId,
+			objective: &RouteObjective
+		) -> Option<(BoundedVec<T::AccountId, T::MaxSize>, RouteMetrics<T>, BalanceOf<T>)> {
+			let route = match Self::cached_route(origin, dest) {
+				Some(route) if *objective == RouteObjective::Cheapest && !Self::crosses_saturated(&route) => route,
+				_ => Self::best_route(origin, dest, objective, true).ok()?,
+			};
+			let metrics = Self::measure_route(&route);
+			let fees = Self::quote_legs(&route).ok()?
+				.iter()
+				.fold(BalanceOf::<T>::zero(), |total, fee| total.saturating_add(*fee));
+			Some((route, metrics, fees))
+		}
+
+		/// Every transit node with its outgoing edges.
+		pub fn transit_network() -> Vec<(T::AccountId, Vec<(T::AccountId, EdgeAttributes<T>)>)> {
+			TransitNodes::<T>::iter_keys()
+				.map(|node| (node.clone(), Edges::<T>::iter_prefix(&node).collect()))
+				.collect()
+		}
+
+		/// Checks that a signed payload was signed within the last interval by a key allowed
+		/// to report on the shipment. Returns the shipment and its holder.
+		fn check_telemetry(payload: &TelemetryPayload<T>) -> Result<(Shipment<T>, T::AccountId), Error<T>> {
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(
+				payload.block_number <= now && payload.block_number.saturating_add(T::TelemetryInterval::get()) > now,
+				Error::<T>::StaleTelemetry);
+			Self::check_reporter(payload.shipment_uid, &payload.public.clone().into_account())
+		}
+
+		/// Checks that `reporter` is the holder of an in-transit shipment or its sensor, and that
+		/// the shipment's rate limit allows a new reading. Returns the shipment and its holder.
+		fn check_reporter(shipment_uid: u64, reporter: &T::AccountId) -> Result<(Shipment<T>, T::AccountId), Error<T>> {
+			let shipment = Self::uid_to_shipment(shipment_uid).ok_or(Error::<T>::ShipmentNotFound)?;
+			let holder = Self::holder_of(&shipment).ok_or(Error::<T>::ShipmentNotInTransit)?;
+			ensure!(
+				reporter == &holder ||
+					Self::shipment_conditions(shipment_uid).and_then(|conditions| conditions.sensor).as_ref() == Some(reporter),
+				Error::<T>::HolderMismatch);
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(now >= Self::next_telemetry_at(shipment_uid), Error::<T>::TelemetryTooFrequent);
+			Ok((shipment, holder))
+		}
+
+		/// Stores a checked reading and flags the first breach of the shipment's condition
+		/// limits during each hop, penalizing the holder.
+		fn record_reading(shipment: &Shipment<T>, holder: T::AccountId, reading: TelemetryReading) {
+			let now = <frame_system::Pallet<T>>::block_number();
+			NextTelemetryAt::<T>::insert(&shipment.uid, now.saturating_add(T::TelemetryInterval::get()));
+			LatestTelemetry::<T>::insert(&shipment.uid, &reading);
+
+			let breached = Self::shipment_conditions(shipment.uid)
+				.filter(|conditions| conditions.is_breached_by(&reading))
+				.map(|conditions| conditions.penalty);
+			if let Some(penalty) = breached {
+				let hop = shipment.owner_index.saturating_sub(1);
+				let first = Breaches::<T>::mutate(&shipment.uid, |hops| {
+					// Bounded by the route length
+					!hops.contains(&hop) && hops.try_push(hop).is_ok()
+				});
+				if first {
+					Self::deposit_event(Event::ConditionsBreached(shipment.uid, holder.clone(), reading.clone()));
+					Self::slash_holder(shipment, penalty);
+				}
+			}
+
+			Self::deposit_event(Event::TelemetryRecorded(shipment.uid, holder, reading));
+		}
+
+		/// Polls the telemetry endpoint for every shipment held by an account with a telemetry
+		/// key in the local keystore, and submits each reading as a signed payload.
+		fn ingest_telemetry(now: T::BlockNumber) -> Result<(), &'static str> {
+			let endpoint = sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, TELEMETRY_ENDPOINT_KEY)
+				.ok_or("No telemetry endpoint configured")?;
+
+			// Poll once per interval, however many blocks the worker runs for
+			let last_poll = StorageValueRef::persistent(b"asset-tracking::last-telemetry-poll");
+			last_poll.mutate(|last: Result<Option<T::BlockNumber>, StorageRetrievalError>| match last {
+				Ok(Some(last)) if now < last.saturating_add(T::TelemetryInterval::get()) => Err("Polled recently"),
+				_ => Ok(now),
+			}).map_err(|error| match error {
+				MutateStorageError::ValueFunctionFailed(error) => error,
+				MutateStorageError::ConcurrentModification(_) => "Concurrent telemetry poll",
+			})?;
+
+			let keys = <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::RuntimeAppPublic::all();
+			ensure!(!keys.is_empty(), "No telemetry keys in the keystore");
+
+			for key in keys {
+				let public: T::Public = <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::GenericPublic::from(key).into();
+				for shipment_uid in Self::shipments_held_by(&public.clone().into_account()) {
+					let reading = match Self::fetch_reading(&endpoint, shipment_uid) {
+						Ok(reading) => reading,
+						Err(error) => {
+							log::warn!(target: "runtime::asset-tracking", "Telemetry of shipment {} unavailable: {:?}", shipment_uid, error);
+							continue;
+						},
+					};
+					let payload = TelemetryPayload::<T> { public: public.clone(), block_number: now, shipment_uid, reading };
+					let _ = Signer::<T, T::AuthorityId>::all_accounts()
+						.with_filter(sp_std::vec![public.clone()])
+						.send_unsigned_transaction(
+							|_| payload.clone(),
+							|payload, signature| Call::submit_telemetry { payload, signature },
+						);
+				}
+			}
+
+			Ok(())
+		}
+
+		/// Fetches `{endpoint}/{shipment_uid}`, which answers with the latest reading as
+		/// `{"latitude": .., "longitude": .., "temperature": .., "humidity": .., "timestamp": ..}`,
+		/// where `humidity` is optional.
+		fn fetch_reading(endpoint: &[u8], shipment_uid: u64) -> Result<TelemetryReading, http::Error> {
+			let mut url = endpoint.to_vec();
+			if url.last() != Some(&b'/') {
+				url.push(b'/');
+			}
+			let digits = url.len();
+			let mut uid = shipment_uid;
+			loop {
+				url.push(b'0' + (uid % 10) as u8);
+				uid /= 10;
+				if uid == 0 {
+					break;
+				}
+			}
+			url[digits..].reverse();
+			let url = sp_std::str::from_utf8(&url).map_err(|_| http::Error::Unknown)?;
+
+			let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(2_000));
+			let pending = http::Request::get(url).deadline(deadline).send().map_err(|_| http::Error::IoError)?;
+			let response = pending.try_wait(deadline).map_err(|_| http::Error::DeadlineReached)??;
+			if response.code != 200 {
+				return Err(http::Error::Unknown);
+			}
+
+			let body = response.body().collect::<Vec<u8>>();
+			Self::parse_reading(&body).ok_or(http::Error::Unknown)
+		}
+
+		/// Parses a reading whose fields are all JSON integers.
+		fn parse_reading(body: &[u8]) -> Option<TelemetryReading> {
+			let body = sp_std::str::from_utf8(body).ok()?;
+			let fields = match lite_json::parse_json(body).ok()? {
+				JsonValue::Object(fields) => fields,
+				_ => return None,
+			};
+			let field = |name: &str| -> Option<i64> {
+				let (_, value) = fields.iter().find(|(key, _)| key.iter().copied().eq(name.chars()))?;
+				match value {
+					JsonValue::Number(number) if number.fraction_length == 0 && number.exponent == 0 => {
+						let integer = i64::try_from(number.integer).ok()?;
+						Some(if number.negative { -integer } else { integer })
+					},
+					_ => None,
+				}
+			};
+
+			Some(TelemetryReading {
+				latitude: field("latitude")?.try_into().ok()?,
+				longitude: field("longitude")?.try_into().ok()?,
+				temperature: field("temperature")?.try_into().ok()?,
+				humidity: match fields.iter().any(|(key, _)| key.iter().copied().eq("humidity".chars())) {
+					true => Some(field("humidity")?.try_into().ok()?),
+					false => None,
+				},
+				timestamp: field("timestamp")?.try_into().ok()?,
+			})
+		}
+
+		// fn set_fees() {}
+
+		// fn route() {}
+
+		// fn get_transit_nodes() {}
+
+		// fn get_transit_status() {}
+	}
+  }
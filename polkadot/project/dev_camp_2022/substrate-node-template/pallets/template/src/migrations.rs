@@ -0,0 +1,228 @@
+//! Storage migrations for the AssetTracking pallet.
+
+use super::*;
+use frame_support::{
+	migration::{storage_iter, storage_key_iter, take_storage_value},
+	pallet_prelude::*,
+	traits::{OnRuntimeUpgrade, PalletInfoAccess, StorageVersion},
+	Blake2_128Concat,
+};
+#[cfg(feature = "try-runtime")]
+use frame_support::{migration::get_storage_value, traits::OnRuntimeUpgradeHelpersExt};
+use sp_std::{collections::btree_set::BTreeSet, marker::PhantomData, vec::Vec};
+
+pub mod v1 {
+	use super::*;
+
+	/// `Shipment` as stored at storage version 0.
+	#[derive(Decode)]
+	pub struct OldShipment<T: Config> {
+		pub creator: T::AccountId,
+		pub fees: Option<BalanceOf<T>>,
+		pub owner_index: u8,
+		pub route: BoundedVec<T::AccountId, T::MaxSize>,
+		pub destination: T::AccountId,
+		pub uid: u64,
+		pub status: ShipmentStatus,
+	}
+
+	/// Secret that reveals a plaintext `UIDToKey` entry carried over from storage version 0.
+	pub fn legacy_secret(key: [u8; 16]) -> [u8; 32] {
+		let mut secret = [0u8; 32];
+		secret[..16].copy_from_slice(&key);
+		secret
+	}
+
+	/// Both keys from the key suffix of a double map hashed with `Blake2_128Concat`.
+	fn decode_double_key<K1: Decode, K2: Decode>(key: &[u8]) -> Option<(K1, K2)> {
+		let mut input = key.get(16..)?;
+		let key1 = K1::decode(&mut input).ok()?;
+		let mut input = input.get(16..)?;
+		let key2 = K2::decode(&mut input).ok()?;
+		Some((key1, key2))
+	}
+
+	/// Nodes with an outgoing `RouteCosts` entry. Removing a node at storage version 0 removed
+	/// every entry from it, but reduced `TransitNodes` to the removed node alone and left the
+	/// entries to it from nodes missing from that list.
+	fn connected_nodes<T: Config>() -> BTreeSet<T::AccountId> {
+		let pallet = <Pallet<T> as PalletInfoAccess>::name().as_bytes();
+		storage_iter::<u32>(pallet, b"RouteCosts")
+			.filter_map(|(key, _)| decode_double_key::<T::AccountId, T::AccountId>(&key))
+			.map(|(from, _)| from)
+			.collect()
+	}
+
+	/// Transit nodes at storage version 0, rebuilt rather than read from the `TransitNodes`
+	/// list: the connected nodes, and the nodes on the routes of in-transit shipments so that
+	/// these can still be handed over.
+	fn legacy_transit_nodes<T: Config>() -> BTreeSet<T::AccountId> {
+		let pallet = <Pallet<T> as PalletInfoAccess>::name().as_bytes();
+		let mut nodes = connected_nodes::<T>();
+		for (_, shipment) in storage_iter::<OldShipment<T>>(pallet, b"UIDToShipment") {
+			if shipment.status == ShipmentStatus::InTransit {
+				nodes.extend(shipment.route.into_iter());
+			}
+		}
+		nodes
+	}
+
+	/// Migrates storage version 0 to 1:
+	/// - `TransitNodes` moves from an unbounded vector to a counted map, replacing the
+	///   hand-maintained `CountForTransitPoints`, and `Nonce` is dropped. As the vector may
+	///   have been corrupted by removing a node, the map is rebuilt by `legacy_transit_nodes`.
+	/// - `RouteCosts` entries become `Edges` with a cost only, dropping those left behind to
+	///   removed nodes. The `RouteVector` cache is cleared, as routes are recomputed on demand.
+	/// - Shipments gain empty fee and deadline schedules, as nothing was ever reserved for them,
+	///   and count as planned for the cheapest route, measured on the migrated edges.
+	/// - Plaintext `UIDToKey` entries become commitments to `legacy_secret(key)`.
+	/// - Every shipment is indexed under its creator and destination. In-transit shipments are
+	///   taken into custody by their holder, and delivered or failed ones are queued for
+	///   archival as if finalized at the upgrade.
+	/// - Deadlines are expired from the current block on.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() >= 1 {
+				return T::DbWeight::get().reads(1);
+			}
+
+			let pallet = <Pallet<T> as PalletInfoAccess>::name().as_bytes();
+			let mut reads: u64 = 1;
+			let mut writes: u64 = 0;
+
+			let _ = take_storage_value::<Vec<T::AccountId>>(pallet, b"TransitNodes", &[]);
+			let _ = take_storage_value::<u64>(pallet, b"CountForTransitPoints", &[]);
+			let _ = take_storage_value::<u32>(pallet, b"Nonce", &[]);
+			reads = reads.saturating_add(3);
+			writes = writes.saturating_add(3);
+
+			// Reads of `RouteCosts` and `UIDToShipment` are counted as they are drained and translated
+			let connected = connected_nodes::<T>();
+			let nodes = legacy_transit_nodes::<T>();
+
+			for node in nodes {
+				if !TransitNodes::<T>::contains_key(&node) {
+					TransitNodes::<T>::insert(&node, ());
+					writes = writes.saturating_add(2);
+				}
+				reads = reads.saturating_add(1);
+			}
+
+			for (key, cost) in storage_iter::<u32>(pallet, b"RouteCosts").drain() {
+				reads = reads.saturating_add(3);
+				writes = writes.saturating_add(1);
+				if let Some((from, to)) = decode_double_key::<T::AccountId, T::AccountId>(&key) {
+					if connected.contains(&to) {
+						Edges::<T>::insert(from, to, EdgeAttributes::with_cost(cost));
+						writes = writes.saturating_add(1);
+					}
+				}
+			}
+
+			let _ = RouteVector::<T>::remove_all(None);
+			writes = writes.saturating_add(1);
+
+			UIDToShipment::<T>::translate::<OldShipment<T>, _>(|_, old| {
+				reads = reads.saturating_add(2 + old.route.len() as u64);
+				writes = writes.saturating_add(1);
+				Some(Shipment {
+					creator: old.creator,
+					fees: old.fees,
+					leg_fees: Default::default(),
+					leg_deadlines: Default::default(),
+					owner_index: old.owner_index,
+					metrics: Pallet::<T>::measure_route(&old.route),
+					route: old.route,
+					destination: old.destination,
+					uid: old.uid,
+					status: old.status,
+					objective: RouteObjective::Cheapest,
+				})
+			});
+
+			let keys: Vec<(u64, [u8; 16])> =
+				storage_key_iter::<u64, [u8; 16], Blake2_128Concat>(pallet, b"UIDToKey").drain().collect();
+			for (uid, key) in keys {
+				reads = reads.saturating_add(2);
+				writes = writes.saturating_add(1);
+				if let Some(shipment) = UIDToShipment::<T>::get(uid) {
+					if shipment.status == ShipmentStatus::InTransit {
+						let commitment = Pallet::<T>::handoff_commitment(uid, shipment.owner_index, &legacy_secret(key));
+						UIDToCommitment::<T>::insert(uid, commitment);
+						writes = writes.saturating_add(1);
+					}
+				}
+			}
+
+			let mut finalized = Vec::new();
+			for shipment in UIDToShipment::<T>::iter_values() {
+				reads = reads.saturating_add(1);
+				writes = writes.saturating_add(2);
+				ShipmentsByCreator::<T>::insert(&shipment.creator, shipment.uid, ());
+				ShipmentsByDestination::<T>::insert(&shipment.destination, shipment.uid, ());
+				match shipment.status {
+					ShipmentStatus::InTransit => {
+						Pallet::<T>::take_custody(&shipment);
+						reads = reads.saturating_add(3);
+						writes = writes.saturating_add(4 + shipment.route.len() as u64);
+					},
+					_ => finalized.push(shipment.uid),
+				}
+			}
+			// Archive in uid order, which roughly follows the age of the shipments
+			finalized.sort();
+			for uid in finalized {
+				Pallet::<T>::queue_archival(uid);
+				reads = reads.saturating_add(1);
+				writes = writes.saturating_add(2);
+			}
+
+			// Blocks before the upgrade have no deadlines to expire nor arrivals to check
+			let now = <frame_system::Pallet<T>>::block_number();
+			DeadlineCursor::<T>::put(now);
+			ArrivalCursor::<T>::put(now);
+			StorageVersion::new(1).put::<Pallet<T>>();
+			writes = writes.saturating_add(3);
+
+			T::DbWeight::get().reads_writes(reads, writes)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			Self::set_temp_storage(legacy_transit_nodes::<T>().len() as u32, "transit_node_count");
+			Self::set_temp_storage(UIDToShipment::<T>::iter_keys().count() as u32, "shipment_count");
+			Ok(())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			let pallet = <Pallet<T> as PalletInfoAccess>::name().as_bytes();
+			ensure!(StorageVersion::get::<Pallet<T>>() == 1, "Storage version not bumped");
+			ensure!(
+				get_storage_value::<u64>(pallet, b"CountForTransitPoints", &[]).is_none(),
+				"CountForTransitPoints not removed"
+			);
+			ensure!(storage_iter::<u32>(pallet, b"RouteCosts").next().is_none(), "RouteCosts not moved into Edges");
+
+			let node_count: u32 = Self::get_temp_storage("transit_node_count").ok_or("Missing node count")?;
+			ensure!(TransitNodes::<T>::count() == node_count, "Transit nodes lost in migration");
+
+			// Decoding every value also checks the new `Shipment` layout
+			let shipment_count: u32 = Self::get_temp_storage("shipment_count").ok_or("Missing shipment count")?;
+			ensure!(UIDToShipment::<T>::iter_values().count() as u32 == shipment_count, "Shipments lost in migration");
+			for shipment in UIDToShipment::<T>::iter_values() {
+				ensure!(
+					ShipmentsByCreator::<T>::contains_key(&shipment.creator, shipment.uid),
+					"Shipment missing from the creator index"
+				);
+				ensure!(
+					ShipmentsByDestination::<T>::contains_key(&shipment.destination, shipment.uid),
+					"Shipment missing from the destination index"
+				);
+			}
+			Ok(())
+		}
+	}
+}
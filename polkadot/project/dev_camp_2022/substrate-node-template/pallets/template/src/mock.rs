@@ -1,10 +1,11 @@
 use crate as pallet_template;
 use frame_support::{
-	traits::{ConstU16, ConstU32, ConstU64, ConstU128}};
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64, ConstU128, GenesisBuild}};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
-	testing::Header,
+	testing::{Header, TestSignature, TestXt, UintAuthorityId},
 	traits::{BlakeTwo256, IdentityLookup},
 };
 
@@ -13,6 +14,11 @@ type Block = frame_system::mocking::MockBlock<Test>;
 
 pub type Balance = u128;
 
+parameter_types! {
+	// Most tests leave transit nodes unbonded so that reserved balances only reflect fees
+	pub static TransitNodeBond: Balance = 0;
+}
+
 // Configure a mock runtime to test the pallet.
 frame_support::construct_runtime!(
 	pub enum Test where
@@ -22,8 +28,8 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		Balances: pallet_balances,
-		AssetTracking: pallet_template::{Pallet, Call, Storage, Event<T>},
-		RandomnessCollectiveFlip: pallet_randomness_collective_flip,
+		Timestamp: pallet_timestamp,
+		AssetTracking: pallet_template::{Pallet, Call, Config<T>, Storage, Event<T>, ValidateUnsigned},
 	}
 );
 
@@ -57,8 +63,53 @@ impl system::Config for Test {
 impl pallet_template::Config for Test {
 	type Event = Event;
 	type Currency = Balances;
-	type KeyRandomNess = RandomnessCollectiveFlip;
 	type MaxSize = frame_support::traits::ConstU32<100>;
+	type MaxRouteSearchIterations = frame_support::traits::ConstU32<1_000>;
+	type MaxEdgesPerNode = ConstU32<8>;
+	type PricePerCostUnit = ConstU128<10>;
+	type LegTimeout = ConstU64<10>;
+	type MaxDeadlinesPerBlock = ConstU32<16>;
+	type MaxReroutesPerBlock = ConstU32<4>;
+	type ArbiterOrigin = frame_system::EnsureRoot<u64>;
+	type NetworkAdminOrigin = frame_system::EnsureRoot<u64>;
+	type TransitNodeBond = TransitNodeBond;
+	type FailureSlash = ConstU128<100>;
+	type CancellationPenalty = ConstU128<15>;
+	type UnbondingPeriod = ConstU64<20>;
+	type RetentionPeriod = ConstU64<20>;
+	type MaxContentIdLen = ConstU32<64>;
+	type MaxMass = ConstU32<1_000_000>;
+	type MaxVolume = ConstU32<2_000_000>;
+	type MaxDeclaredValue = ConstU128<500_000>;
+	type MaxChildren = ConstU32<10>;
+	type AuthorityId = TestAuthId;
+	type TelemetryInterval = ConstU64<5>;
+	type TelemetryPriority = ConstU64<100>;
+	type MaxSealedSecretLen = ConstU32<128>;
+	type WeightInfo = ();
+}
+
+pub struct TestAuthId;
+
+impl frame_system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+	type GenericPublic = UintAuthorityId;
+}
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+pub type Extrinsic = TestXt<Call, ()>;
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = Extrinsic;
 }
 
 impl pallet_balances::Config for Test {
@@ -75,9 +126,20 @@ impl pallet_balances::Config for Test {
 	type WeightInfo = pallet_balances::weights::SubstrateWeight<Test>;
 }
 
-impl pallet_randomness_collective_flip::Config for Test {}
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<5>;
+	type WeightInfo = ();
+}
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: (1..=5).map(|account| (account, 1_000_000)).collect(),
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	t.into()
 }
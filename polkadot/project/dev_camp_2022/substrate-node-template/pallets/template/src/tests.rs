@@ -1,5 +1,30 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok,bounded_vec};
+use crate::{
+	mock::*, MAX_REPORT_BLOCKS, MAX_SHIPMENTS_PAGE, ConditionLimits, DisputeOutcome, EdgeAttributes, EdgeProfile, EmissionsLedger, Error, HandlingFlags, RejectionReason, RouteMetrics, RouteObjective,
+	ShipmentMetadata, ShipmentRole, ShipmentStatus, ShipmentsByCreator, ShipmentsByRouteNode, TelemetryPayload, TelemetryReading, TransportMode,
+};
+use codec::Decode;
+use frame_support::{assert_noop, assert_ok,bounded_vec, traits::{GenesisBuild, Hooks}};
+use sp_core::{
+	offchain::{
+		testing::{PendingRequest, TestOffchainExt, TestTransactionPoolExt},
+		OffchainDbExt, OffchainWorkerExt, StorageKind, TransactionPoolExt,
+	},
+	H256,
+};
+use sp_runtime::{
+	testing::UintAuthorityId,
+	traits::ValidateUnsigned,
+	transaction_validity::{InvalidTransaction, TransactionSource},
+};
+
+// Secret revealed by `route[hop]` when taking custody
+fn secret(hop: u8) -> [u8; 32] {
+	[hop; 32]
+}
+
+fn commitment(uid: u64, hop: u8) -> H256 {
+	AssetTracking::handoff_commitment(uid, hop, &secret(hop))
+}
 
 #[test]
 fn it_creates_transit_node() {
@@ -37,3 +62,1156 @@ fn it_removes_transit_node() {
 
 
 
+#[test]
+fn it_routes_shipment_over_cheapest_path() {
+	new_test_ext().execute_with(|| {
+		// 1 -(1)- 2 -(1)- 3 -(1)- 4 and a direct but expensive 1 -(10)- 4
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),4,bounded_vec![(3,1),(1,10)]));
+
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),4,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		let shipment = AssetTracking::uid_to_shipment(1).unwrap();
+		assert_eq!(shipment.route.to_vec(), vec![1,2,3,4]);
+		// Route is cached for the lane
+		assert_eq!(AssetTracking::cached_route(&1,&4).map(|route| route.into_inner()), Some(vec![1,2,3,4]));
+
+		// Changing the network starts a new route generation and the direct lane becomes cheapest
+		let generation = AssetTracking::route_generation();
+		assert_ok!(AssetTracking::update_neighbour(Origin::root(),1,4,2));
+		assert_eq!(AssetTracking::route_generation(), generation + 1);
+		assert_eq!(AssetTracking::cached_route(&1,&4), None);
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),4,commitment(2,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_eq!(AssetTracking::uid_to_shipment(2).unwrap().route.to_vec(), vec![1,4]);
+	});
+}
+
+#[test]
+fn it_fails_to_route_disconnected_nodes() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![]));
+		assert_noop!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest),
+		Error::<Test>::InvalidRoute);
+	});
+}
+
+#[test]
+fn it_reserves_and_pays_out_fees_per_leg() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,3)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,5)]));
+
+		// (3 + 5) cost units at 10 per unit
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		let shipment = AssetTracking::uid_to_shipment(1).unwrap();
+		assert_eq!(shipment.fees, Some(80));
+		assert_eq!(Balances::reserved_balance(1), 80);
+
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+		assert_eq!(Balances::reserved_balance(1), 50);
+		assert_eq!(Balances::free_balance(2), 1_000_030);
+
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(3),1,secret(2),None,bounded_vec![],None));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(3), 1_000_050);
+	});
+}
+
+#[test]
+fn it_fails_overdue_shipments_and_refunds_fees() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,3)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_eq!(Balances::reserved_balance(1), 30);
+
+		// First leg is due at block 1 + 10
+		AssetTracking::on_initialize(10);
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().status, ShipmentStatus::InTransit);
+		AssetTracking::on_initialize(11);
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().status, ShipmentStatus::Failed);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(AssetTracking::shipment_commitment(1), None);
+	});
+}
+
+#[test]
+fn it_resolves_disputes() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,3)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+
+		// Only the creator can dispute
+		assert_noop!(AssetTracking::open_dispute(Origin::signed(2),1), Error::<Test>::UnauthorizedCaller);
+		assert_ok!(AssetTracking::open_dispute(Origin::signed(1),1));
+
+		// Custody is frozen and timeouts are ignored while disputed
+		assert_noop!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),None,bounded_vec![],None),
+		Error::<Test>::ShipmentDisputed);
+		AssetTracking::on_initialize(11);
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().status, ShipmentStatus::InTransit);
+
+		assert_noop!(AssetTracking::resolve_dispute(Origin::signed(1),1,DisputeOutcome::Creator),
+		sp_runtime::DispatchError::BadOrigin);
+		assert_ok!(AssetTracking::resolve_dispute(Origin::root(),1,DisputeOutcome::Creator));
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().status, ShipmentStatus::Failed);
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn it_rejects_disputes_on_packed_shipments() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,3)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(2,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::pack_shipments(Origin::signed(1),1,bounded_vec![2]));
+
+		// The parent is disputed instead, which freezes the child with it
+		assert_noop!(AssetTracking::open_dispute(Origin::signed(1),2), Error::<Test>::ShipmentPacked);
+		assert_ok!(AssetTracking::open_dispute(Origin::signed(1),1));
+		assert_noop!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),None,bounded_vec![],None),
+		Error::<Test>::ShipmentDisputed);
+	});
+}
+
+#[test]
+fn it_verifies_handoff_secrets() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,1)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![1,2,3],None,None,RouteObjective::Cheapest));
+
+		// Only the commitment is stored on-chain
+		assert_eq!(AssetTracking::shipment_commitment(1), Some(commitment(1,1)));
+		System::assert_last_event(Event::AssetTracking(crate::Event::HandoffSecretSealed(1,2,bounded_vec![1,2,3])));
+
+		// Wrong secret, or the right secret for another hop, is rejected
+		assert_noop!(AssetTracking::update_shipment(Origin::signed(2),1,secret(2),Some(commitment(1,2)),bounded_vec![],None),
+		Error::<Test>::InvalidKey);
+		// The next holder has to commit to the following handoff
+		assert_noop!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),None,bounded_vec![],None),
+		Error::<Test>::MissingCommitment);
+
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+		assert_eq!(AssetTracking::shipment_commitment(1), Some(commitment(1,2)));
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(3),1,secret(2),None,bounded_vec![],None));
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().status, ShipmentStatus::AwaitingAcceptance);
+		// Arrived shipments are not handed over again
+		assert_noop!(AssetTracking::update_shipment(Origin::signed(3),1,secret(2),None,bounded_vec![],None),
+		Error::<Test>::ShipmentNotInTransit);
+	});
+}
+
+#[test]
+fn it_answers_shipment_queries() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,3)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,5)]));
+
+		let (route, metrics, fees) = AssetTracking::quote_route(&1, &3, &RouteObjective::Cheapest).unwrap();
+		assert_eq!((route.to_vec(), metrics.cost, fees), (vec![1,2,3], 8, 80));
+		// Quoting does not populate the route cache
+		assert_eq!(AssetTracking::cached_route(&1,&3), None);
+		assert_ok!(AssetTracking::set_edge_duration(Origin::root(),2,3,Some(4)));
+		let (_, mut edges) = AssetTracking::transit_network().into_iter().find(|(node, _)| *node == 2).unwrap();
+		edges.sort_by_key(|(neighbour, _)| *neighbour);
+		assert_eq!(edges, vec![
+			(1, EdgeAttributes::with_cost(3)),
+			(3, EdgeAttributes { cost: 5, transit_time: Some(4), profile: None }),
+		]);
+
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_eq!(AssetTracking::shipments_held_by(&1), vec![1]);
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+		assert_eq!(AssetTracking::shipments_held_by(&1), Vec::<u64>::new());
+		assert_eq!(AssetTracking::shipments_held_by(&2), vec![1]);
+	});
+}
+
+#[test]
+fn it_builds_transit_network_at_genesis() {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 1_000_000), (2, 1_000_000), (3, 1_000_000)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	crate::GenesisConfig::<Test> {
+		transit_nodes: vec![(1, vec![]), (2, vec![(1, 3)]), (3, vec![(2, 5), (1, 20)])],
+		shipments: vec![(1, 3, commitment(1, 1))],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	sp_io::TestExternalities::from(t).execute_with(|| {
+		assert_eq!(AssetTracking::count_for_transit_point(), 3);
+		assert_eq!(AssetTracking::edge(3, 2).map(|edge| edge.cost), Some(5));
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().route.to_vec(), vec![1, 2, 3]);
+		assert_eq!(Balances::reserved_balance(1), 80);
+	});
+}
+
+#[test]
+fn it_migrates_storage_to_v1() {
+	use codec::Encode;
+	use frame_support::{migration::put_storage_value, traits::{OnRuntimeUpgrade, StorageVersion}, Blake2_128Concat, StorageHasher};
+	use crate::migrations::v1::{legacy_secret, MigrateToV1};
+
+	new_test_ext().execute_with(|| {
+		System::set_block_number(7);
+		let pallet = b"AssetTracking";
+		let uid = |uid: u64| Blake2_128Concat::hash(&uid.encode());
+		let edge = |from: u64, to: u64| [Blake2_128Concat::hash(&from.encode()), Blake2_128Concat::hash(&to.encode())].concat();
+		StorageVersion::new(0).put::<AssetTracking>();
+		// Removing 4 and then 5, which was only connected to 3, reduced the list to the last
+		// node removed and left the edge from 3 to 5 behind
+		put_storage_value(pallet, b"TransitNodes", &[], vec![5u64]);
+		put_storage_value(pallet, b"CountForTransitPoints", &[], 3u64);
+		put_storage_value(pallet, b"Nonce", &[], 7u32);
+		for (from, to, cost) in [(1u64, 2u64, 3u32), (2, 1, 3), (2, 3, 5), (3, 2, 5), (3, 5, 1)] {
+			put_storage_value(pallet, b"RouteCosts", &edge(from, to), cost);
+		}
+		put_storage_value(pallet, b"RouteVector", &edge(1, 3), vec![1u64, 2, 3]);
+		put_storage_value(pallet, b"UIDToShipment", &uid(1),
+			(1u64, Some(50u128), 1u8, vec![1u64, 2, 3], 3u64, 1u64, ShipmentStatus::InTransit));
+		put_storage_value(pallet, b"UIDToShipment", &uid(2),
+			(2u64, Some(30u128), 2u8, vec![2u64, 3], 3u64, 2u64, ShipmentStatus::Delivered));
+		put_storage_value(pallet, b"UIDToKey", &uid(1), [9u8; 16]);
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::get::<AssetTracking>(), 1);
+		assert_eq!(AssetTracking::count_for_transit_point(), 3);
+		assert!([1, 2, 3].iter().all(AssetTracking::is_transit_node));
+		assert!(!AssetTracking::is_transit_node(&4) && !AssetTracking::is_transit_node(&5));
+		assert_eq!(AssetTracking::edge(2, 3), Some(EdgeAttributes { cost: 5, transit_time: None, profile: None }));
+		assert_eq!(AssetTracking::edge(3, 5), None);
+		assert_eq!(AssetTracking::cached_route(&1, &3), None);
+		assert_eq!(AssetTracking::deadline_cursor(), 7);
+
+		// Shipments are planned for the cheapest route and measured on the migrated edges
+		let shipment = AssetTracking::uid_to_shipment(1).unwrap();
+		assert_eq!((shipment.route.to_vec(), shipment.owner_index, shipment.fees), (vec![1, 2, 3], 1, Some(50)));
+		assert!(shipment.leg_fees.is_empty());
+		assert_eq!((shipment.objective, shipment.metrics),
+		(RouteObjective::Cheapest, RouteMetrics { cost: 8, transit_time: 20, emissions: 0 }));
+		assert_eq!(
+			AssetTracking::shipment_commitment(1),
+			Some(AssetTracking::handoff_commitment(1, 1, &legacy_secret([9u8; 16])))
+		);
+
+		// Shipments are indexed, in-transit ones held and finalized ones queued for archival
+		let mut bound_for_3 = AssetTracking::shipments_by(ShipmentRole::Destination, &3, None, 10);
+		bound_for_3.sort();
+		assert_eq!(bound_for_3, vec![1, 2]);
+		assert_eq!(AssetTracking::shipments_by(ShipmentRole::Holder, &1, None, 10), vec![1]);
+		assert_eq!(AssetTracking::custody_count(1), 1);
+		assert_eq!(ShipmentsByRouteNode::<Test>::iter_key_prefix(3).collect::<Vec<_>>(), vec![1]);
+		assert_eq!(AssetTracking::archival_queue(0), Some((2, 7)));
+
+		// The old key now reveals the secret for the next handoff
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,legacy_secret([9u8; 16]),Some(commitment(1,2)),bounded_vec![],None));
+		assert_eq!((AssetTracking::custody_count(1), AssetTracking::custody_count(2)), (0, 1));
+	});
+}
+
+#[test]
+fn it_reroutes_shipments_around_removed_nodes() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),4,bounded_vec![(1,5),(3,5)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_eq!(Balances::reserved_balance(1), 20);
+
+		// Remaining legs are re-planned and re-quoted around the removed node
+		assert_ok!(AssetTracking::remove_transit_node(Origin::root(),2));
+		System::assert_has_event(Event::AssetTracking(crate::Event::ShipmentRerouted(1,bounded_vec![1,2,3],bounded_vec![1,4,3])));
+		let shipment = AssetTracking::uid_to_shipment(1).unwrap();
+		assert_eq!((shipment.status, shipment.fees), (ShipmentStatus::InTransit, Some(100)));
+		assert_eq!(Balances::reserved_balance(1), 100);
+
+		// The pending commitment is bound to the hop, so the new next node can take custody
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(4),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+		assert_eq!(Balances::reserved_balance(1), 50);
+
+		// No path is left from the holder to the destination
+		assert_ok!(AssetTracking::update_neighbour(Origin::root(),4,3,7));
+		assert_eq!(Balances::reserved_balance(1), 70);
+		assert_ok!(AssetTracking::remove_transit_node(Origin::root(),3));
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().status, ShipmentStatus::Failed);
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn it_lets_operators_manage_their_own_nodes() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+
+		// Unregistered accounts cannot touch the network
+		assert_noop!(AssetTracking::create_new_transit_node(Origin::signed(5),2,bounded_vec![]), Error::<Test>::NotOperator);
+		assert_ok!(AssetTracking::register_operator(Origin::root(),5));
+
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::signed(5),2,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::signed(5),3,bounded_vec![(2,4)]));
+		assert_eq!(AssetTracking::node_operator(2), Some(5));
+		assert_ok!(AssetTracking::update_neighbour(Origin::signed(5),2,3,6));
+
+		// Node 1 belongs to the admin until it is handed over
+		assert_noop!(AssetTracking::create_new_transit_node(Origin::signed(5),4,bounded_vec![(1,2)]), Error::<Test>::NotNodeOperator);
+		assert_noop!(AssetTracking::update_neighbour(Origin::signed(5),1,2,3), Error::<Test>::NotNodeOperator);
+		assert_ok!(AssetTracking::set_node_operator(Origin::root(),1,Some(5)));
+		assert_ok!(AssetTracking::update_neighbour(Origin::signed(5),1,2,3));
+
+		assert_ok!(AssetTracking::deregister_operator(Origin::root(),5));
+		assert_noop!(AssetTracking::remove_transit_node(Origin::signed(5),3), Error::<Test>::NotOperator);
+		assert_ok!(AssetTracking::remove_transit_node(Origin::root(),3));
+		assert_eq!(AssetTracking::node_operator(3), None);
+	});
+}
+
+#[test]
+fn it_slashes_bonds_of_failed_holders() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		TransitNodeBond::set(1_000);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,3)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,5)]));
+		assert_eq!(Balances::reserved_balance(2), 1_000);
+
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+
+		// Node 2 misses its deadline and compensates the creator
+		AssetTracking::on_initialize(21);
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().status, ShipmentStatus::Failed);
+		assert_eq!(AssetTracking::bonds(2), Some(900));
+		assert_eq!(Balances::reserved_balance(2), 900);
+		assert_eq!(Balances::free_balance(1), 1_000_000 - 1_000 - 30 + 100);
+
+		// Bonds are only released after leaving the network and cooling off
+		assert_noop!(AssetTracking::withdraw_bond(Origin::signed(2),2), Error::<Test>::StillTransitNode);
+		System::set_block_number(21);
+		assert_ok!(AssetTracking::remove_transit_node(Origin::root(),2));
+		assert_noop!(AssetTracking::withdraw_bond(Origin::signed(2),2), Error::<Test>::StillUnbonding);
+		System::set_block_number(41);
+		assert_ok!(AssetTracking::withdraw_bond(Origin::signed(2),2));
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(AssetTracking::bonds(2), None);
+	});
+}
+
+#[test]
+fn it_logs_custody_until_delivery() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		Timestamp::set_timestamp(6_000);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,3)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,5)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+
+		System::set_block_number(2);
+		Timestamp::set_timestamp(12_000);
+		let note = H256::repeat_byte(7);
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],Some(note)));
+		System::assert_has_event(Event::AssetTracking(crate::Event::CustodyTransferred(1,1,2)));
+
+		let log = AssetTracking::custody_log(1);
+		assert_eq!(log.iter().map(|record| (record.hop, record.holder, record.block, record.moment)).collect::<Vec<_>>(),
+		vec![(0,1,1,6_000),(1,2,2,12_000)]);
+		assert_eq!(log[1].note, Some(note));
+
+		// The log is pruned once the shipment is finalized
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(3),1,secret(2),None,bounded_vec![],None));
+		System::assert_has_event(Event::AssetTracking(crate::Event::CustodyTransferred(1,2,3)));
+		assert!(AssetTracking::custody_log(1).is_empty());
+	});
+}
+
+#[test]
+fn it_stores_validated_shipment_metadata() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,3)]));
+
+		let metadata = ShipmentMetadata::<Test> {
+			content: vec![0xab; 46].try_into().unwrap(),
+			mass: 12_500,
+			volume: 40_000,
+			declared_value: 2_000,
+			flags: HandlingFlags(HandlingFlags::FRAGILE | HandlingFlags::KEEP_UPRIGHT),
+		};
+		let invalid = |update: fn(&mut ShipmentMetadata<Test>)| {
+			let mut invalid = metadata.clone();
+			update(&mut invalid);
+			Some(invalid)
+		};
+		assert_noop!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(1,1),bounded_vec![],
+		invalid(|metadata| metadata.content = bounded_vec![]),None,RouteObjective::Cheapest), Error::<Test>::EmptyContent);
+		assert_noop!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(1,1),bounded_vec![],
+		invalid(|metadata| metadata.mass = 1_000_001),None,RouteObjective::Cheapest), Error::<Test>::MassTooHigh);
+		assert_noop!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(1,1),bounded_vec![],
+		invalid(|metadata| metadata.declared_value = 500_001),None,RouteObjective::Cheapest), Error::<Test>::DeclaredValueTooHigh);
+		assert_noop!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(1,1),bounded_vec![],
+		invalid(|metadata| metadata.flags = HandlingFlags(1 << 7)),None,RouteObjective::Cheapest), Error::<Test>::UnknownHandlingFlags);
+
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(1,1),bounded_vec![],Some(metadata.clone()),None,RouteObjective::Cheapest));
+		assert_eq!(AssetTracking::shipment_metadata(1), Some(metadata));
+		assert!(AssetTracking::shipment_metadata(1).unwrap().flags.contains(HandlingFlags::FRAGILE));
+	});
+}
+
+#[test]
+fn it_packs_and_unpacks_shipments() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),4,bounded_vec![(3,1)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),4,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(2,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),4,commitment(3,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+
+		assert_noop!(AssetTracking::pack_shipments(Origin::signed(2),1,bounded_vec![2,3]), Error::<Test>::HolderMismatch);
+		assert_noop!(AssetTracking::pack_shipments(Origin::signed(1),1,bounded_vec![2,2]), Error::<Test>::InvalidChild);
+		assert_ok!(AssetTracking::pack_shipments(Origin::signed(1),1,bounded_vec![2,3]));
+		assert_noop!(AssetTracking::update_shipment(Origin::signed(2),2,secret(1),Some(commitment(2,2)),bounded_vec![],None),
+		Error::<Test>::ShipmentPacked);
+
+		// Children move with the parent's handoffs
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+		let mut held = AssetTracking::shipments_held_by(&2);
+		held.sort();
+		assert_eq!(held, vec![1,2,3]);
+
+		// Unpacked at node 2, the child continues on its own route from there
+		assert_ok!(AssetTracking::unpack_shipment(Origin::signed(2),2,Some(commitment(2,1)),bounded_vec![]));
+		assert_eq!(AssetTracking::uid_to_shipment(2).unwrap().route.to_vec(), vec![2,3]);
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(3),2,secret(1),None,bounded_vec![],None));
+		assert_eq!(AssetTracking::uid_to_shipment(2).unwrap().status, ShipmentStatus::AwaitingAcceptance);
+
+		// Children still packed at the parent's destination are delivered there
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(3),1,secret(2),Some(commitment(1,3)),bounded_vec![],None));
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(4),1,secret(3),None,bounded_vec![],None));
+		assert_ok!(AssetTracking::unpack_shipment(Origin::signed(4),3,None,bounded_vec![]));
+		assert_eq!(AssetTracking::uid_to_shipment(3).unwrap().status, ShipmentStatus::AwaitingAcceptance);
+		assert!(AssetTracking::children(1).is_empty());
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn it_fails_packed_shipments_with_their_parent() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		for uid in 1..=4u64 {
+			assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(uid,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		}
+		assert_ok!(AssetTracking::pack_shipments(Origin::signed(1),1,bounded_vec![2,3]));
+		assert_eq!(AssetTracking::custody_count(1), 2);
+
+		// Only the parent's custody is released, the children's was released when packing
+		assert_ok!(AssetTracking::open_dispute(Origin::signed(1),1));
+		assert_ok!(AssetTracking::resolve_dispute(Origin::root(),1,DisputeOutcome::Creator));
+		assert!((1..=3u64).all(|uid| AssetTracking::uid_to_shipment(uid).unwrap().status == ShipmentStatus::Failed));
+		assert_eq!(AssetTracking::custody_count(1), 1);
+		assert_eq!(AssetTracking::shipments_by(ShipmentRole::Holder,&1,None,10), vec![4]);
+		assert!(AssetTracking::children(1).is_empty());
+		assert_eq!((AssetTracking::parent_of(2), AssetTracking::parent_of(3)), (None, None));
+	});
+}
+
+#[test]
+fn it_keeps_shipments_packed_when_unpacking_fails() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,3)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(2,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::pack_shipments(Origin::signed(1),1,bounded_vec![2]));
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),None,bounded_vec![],None));
+		assert_eq!(Balances::reserved_balance(1), 30);
+
+		// A full custody log fails the delivery after the child's fees were refunded
+		let record = AssetTracking::custody_log(2)[0].clone();
+		while crate::CustodyLog::<Test>::try_append(2, record.clone()).is_ok() {}
+		assert_noop!(AssetTracking::unpack_shipment(Origin::signed(2),2,None,bounded_vec![]), Error::<Test>::TransitNodesOverFlow);
+		assert_eq!(AssetTracking::parent_of(2), Some(1));
+		assert_eq!(AssetTracking::uid_to_shipment(2).unwrap().status, ShipmentStatus::InTransit);
+	});
+}
+
+#[test]
+fn it_ingests_telemetry_from_the_offchain_worker() {
+	let (offchain, offchain_state) = TestOffchainExt::new();
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainDbExt::new(offchain.clone()));
+	t.register_extension(OffchainWorkerExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+	// The keystore of node 1
+	UintAuthorityId::set_all_keys(vec![1u64]);
+
+	offchain_state.write().expect_request(PendingRequest {
+		method: "GET".into(),
+		uri: "http://localhost:8080/telemetry/1".into(),
+		response: Some(br#"{"latitude":52520008,"longitude":-13404954,"temperature":-1850,"timestamp":1666000000000}"#.to_vec()),
+		sent: true,
+		..Default::default()
+	});
+
+	t.execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,3)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, crate::TELEMETRY_ENDPOINT_KEY, b"http://localhost:8080/telemetry");
+
+		AssetTracking::offchain_worker(1);
+		let tx = pool_state.write().transactions.pop().unwrap();
+		assert!(pool_state.read().transactions.is_empty());
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		assert_eq!(tx.signature, None);
+		let call = match tx.call {
+			Call::AssetTracking(call) => call,
+			_ => panic!("Unexpected call"),
+		};
+		let (payload, signature) = match call.clone() {
+			crate::Call::submit_telemetry { payload, signature } => (payload, signature),
+			_ => panic!("Unexpected call"),
+		};
+		let reading = TelemetryReading {
+			latitude: 52_520_008, longitude: -13_404_954, temperature: -1_850, humidity: None, timestamp: 1_666_000_000_000,
+		};
+		assert_eq!(payload.reading, reading);
+		assert!(AssetTracking::validate_unsigned(TransactionSource::External, &call).is_ok());
+
+		// Only the holder's key may report on a shipment
+		let forged = crate::Call::submit_telemetry {
+			payload: TelemetryPayload { public: UintAuthorityId(2), ..payload.clone() },
+			signature: signature.clone(),
+		};
+		assert_eq!(AssetTracking::validate_unsigned(TransactionSource::External, &forged), InvalidTransaction::BadProof.into());
+
+		assert_ok!(AssetTracking::submit_telemetry(Origin::none(),payload.clone(),signature.clone()));
+		assert_eq!(AssetTracking::latest_telemetry(1), Some(reading.clone()));
+		System::assert_last_event(Event::AssetTracking(crate::Event::TelemetryRecorded(1,1,reading)));
+
+		// Readings and polls are rate limited to one per interval
+		assert_noop!(AssetTracking::submit_telemetry(Origin::none(),payload,signature), Error::<Test>::TelemetryTooFrequent);
+		System::set_block_number(2);
+		AssetTracking::offchain_worker(2);
+		assert!(pool_state.read().transactions.is_empty());
+	});
+}
+
+#[test]
+fn it_flags_and_penalizes_condition_breaches() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		TransitNodeBond::set(1_000);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,3)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,5)]));
+
+		let conditions = ConditionLimits::<Test> {
+			min_temperature: 200,
+			max_temperature: 800,
+			min_humidity: 2_000,
+			max_humidity: 6_000,
+			penalty: 40,
+			sensor: Some(5),
+		};
+		assert_noop!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,
+		Some(ConditionLimits { min_temperature: 900, ..conditions.clone() }),RouteObjective::Cheapest), Error::<Test>::InvalidConditionLimits);
+		assert_noop!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,
+		Some(ConditionLimits { penalty: 101, ..conditions.clone() }),RouteObjective::Cheapest), Error::<Test>::PenaltyTooHigh);
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,Some(conditions),RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+
+		let reading = |temperature, humidity| TelemetryReading { temperature, humidity, ..Default::default() };
+
+		// Only the holder and the shipment's sensor may report, once per interval
+		assert_noop!(AssetTracking::submit_reading(Origin::signed(4),1,reading(500,None)), Error::<Test>::HolderMismatch);
+		assert_ok!(AssetTracking::submit_reading(Origin::signed(5),1,reading(500,Some(4_000))));
+		assert_noop!(AssetTracking::submit_reading(Origin::signed(2),1,reading(500,None)), Error::<Test>::TelemetryTooFrequent);
+		assert!(AssetTracking::breaches(1).is_empty());
+
+		// A breach flags the hop and penalizes its holder
+		System::set_block_number(6);
+		assert_ok!(AssetTracking::submit_reading(Origin::signed(2),1,reading(950,None)));
+		System::assert_has_event(Event::AssetTracking(crate::Event::ConditionsBreached(1,2,reading(950,None))));
+		assert_eq!(AssetTracking::breaches(1).to_vec(), vec![1]);
+		assert_eq!(AssetTracking::bonds(2), Some(960));
+
+		// Further breaches during the same hop are not penalized again
+		System::set_block_number(11);
+		assert_ok!(AssetTracking::submit_reading(Origin::signed(5),1,reading(500,Some(7_000))));
+		assert_eq!(AssetTracking::latest_telemetry(1), Some(reading(500,Some(7_000))));
+		assert_eq!(AssetTracking::bonds(2), Some(960));
+		assert_eq!(Balances::free_balance(1), 1_000_000 - 1_000 - 80 + 40);
+	});
+}
+
+#[test]
+fn it_estimates_arrivals_from_edge_durations() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,3)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,5)]));
+		assert_noop!(AssetTracking::set_edge_duration(Origin::root(),1,3,Some(4)), Error::<Test>::NotNeighbours);
+		assert_ok!(AssetTracking::set_edge_duration(Origin::root(),1,2,Some(4)));
+		assert_ok!(AssetTracking::set_edge_duration(Origin::root(),3,2,Some(6)));
+		assert_eq!(AssetTracking::edge(2,1).and_then(|edge| edge.transit_time), Some(4));
+
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_eq!(AssetTracking::planned_arrivals(1).to_vec(), vec![5,11]);
+		assert_eq!(AssetTracking::current_eta(1), Some(11));
+
+		// Early handoffs bring the ETA forward
+		System::set_block_number(3);
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+		assert_eq!(AssetTracking::current_eta(1), Some(9));
+
+		// Overdue legs are reported at their planned arrival and push the ETA back
+		System::set_block_number(11);
+		AssetTracking::on_initialize(11);
+		System::assert_has_event(Event::AssetTracking(crate::Event::ShipmentLate(1,2,11)));
+		System::set_block_number(12);
+		assert_eq!(AssetTracking::current_eta(1), Some(12));
+
+		// Edges without a duration are expected to take `LegTimeout`
+		assert_ok!(AssetTracking::set_edge_duration(Origin::root(),2,3,None));
+		assert_eq!(AssetTracking::current_eta(1), Some(13));
+	});
+}
+
+#[test]
+fn it_routes_around_saturated_nodes() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(1,5)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),4,bounded_vec![(2,1),(3,5)]));
+		assert_ok!(AssetTracking::set_capacity(Origin::root(),2,Some(1)));
+
+		// Shipments on their way to a node count against its capacity
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),4,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().route.to_vec(), vec![1,2,4]);
+		assert_eq!(AssetTracking::inbound_shipments(&2), vec![(1,10)]);
+		assert!(AssetTracking::is_saturated(&2));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),4,commitment(2,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_eq!(AssetTracking::uid_to_shipment(2).unwrap().route.to_vec(), vec![1,3,4]);
+		assert_eq!(AssetTracking::custody_count(1), 2);
+
+		// Saturated destinations accept no new shipments
+		assert_ok!(AssetTracking::set_capacity(Origin::root(),4,Some(0)));
+		assert_noop!(AssetTracking::create_shipment(Origin::signed(1),4,commitment(3,1),bounded_vec![],None,None,RouteObjective::Cheapest),
+		Error::<Test>::NodeSaturated);
+		assert_ok!(AssetTracking::set_capacity(Origin::root(),4,None));
+
+		// Handoffs move shipments from the inbound queue into custody
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+		assert_eq!(AssetTracking::custody_count(1), 1);
+		assert_eq!(AssetTracking::custody_count(2), 1);
+		assert!(AssetTracking::inbound_shipments(&2).is_empty());
+		assert_eq!(AssetTracking::inbound_shipments(&4), vec![(1,20)]);
+
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(4),1,secret(2),None,bounded_vec![],None));
+		assert_eq!(AssetTracking::custody_count(2), 0);
+		assert!(AssetTracking::inbound_shipments(&4).is_empty());
+		assert!(!AssetTracking::is_saturated(&2));
+	});
+}
+
+#[test]
+fn it_returns_rejected_shipments_to_their_creator() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,1)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_noop!(AssetTracking::accept_shipment(Origin::signed(3),1), Error::<Test>::NotAwaitingAcceptance);
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(3),1,secret(2),None,bounded_vec![],None));
+
+		// Only the destination decides, and the return starts at it with fresh custody keys
+		assert_noop!(AssetTracking::reject_shipment(Origin::signed(2),1,RejectionReason::Damaged,commitment(2,1),bounded_vec![]),
+		Error::<Test>::UnauthorizedCaller);
+		assert_ok!(AssetTracking::reject_shipment(Origin::signed(3),1,RejectionReason::Damaged,commitment(2,1),bounded_vec![]));
+		System::assert_last_event(Event::AssetTracking(crate::Event::ShipmentRejected(1,RejectionReason::Damaged,2)));
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().status, ShipmentStatus::Rejected);
+		assert_eq!(AssetTracking::rejections(1), Some((RejectionReason::Damaged,2)));
+		assert_eq!(AssetTracking::return_of(2), Some(1));
+		let returned = AssetTracking::uid_to_shipment(2).unwrap();
+		assert_eq!((returned.creator, returned.destination), (1, 1));
+		assert_eq!(returned.route.to_vec(), vec![3,2,1]);
+		assert_eq!(AssetTracking::shipment_commitment(2), Some(commitment(2,1)));
+		assert_noop!(AssetTracking::accept_shipment(Origin::signed(3),1), Error::<Test>::NotAwaitingAcceptance);
+
+		// The creator receives the return and cannot bounce it back
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),2,secret(1),Some(commitment(2,2)),bounded_vec![],None));
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(1),2,secret(2),None,bounded_vec![],None));
+		assert_noop!(AssetTracking::reject_shipment(Origin::signed(1),2,RejectionReason::Other,commitment(3,1),bounded_vec![]),
+		Error::<Test>::CannotRejectReturn);
+		assert_ok!(AssetTracking::accept_shipment(Origin::signed(1),2));
+		assert_eq!(AssetTracking::uid_to_shipment(2).unwrap().status, ShipmentStatus::Delivered);
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn it_archives_cancelled_returns_with_their_rejection() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,1)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(3),1,secret(2),None,bounded_vec![],None));
+		assert_ok!(AssetTracking::reject_shipment(Origin::signed(3),1,RejectionReason::Damaged,commitment(2,1),bounded_vec![]));
+
+		// The cancelled return keeps its link to the rejected shipment until both are archived
+		assert_ok!(AssetTracking::cancel_shipment(Origin::signed(1),2));
+		assert_eq!(AssetTracking::return_of(2), Some(1));
+		let records = AssetTracking::shipment_records(2);
+		assert_eq!(records.return_of, Some(1));
+		let record_hash = AssetTracking::archive_hash(&AssetTracking::uid_to_shipment(2).unwrap(), &records);
+
+		AssetTracking::on_idle(21, u64::MAX);
+		assert_eq!(AssetTracking::rejections(1), None);
+		assert_eq!(AssetTracking::return_of(2), None);
+		assert!(AssetTracking::archived_shipments(1).is_some());
+		assert_eq!(AssetTracking::archived_shipments(2), Some(record_hash));
+		assert!(AssetTracking::shipments_by(ShipmentRole::Creator,&1,None,10).is_empty());
+	});
+}
+
+#[test]
+fn it_cancels_and_amends_shipments() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),4,bounded_vec![(3,1)]));
+
+		// Cancelling before the first handoff refunds every fee
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_noop!(AssetTracking::cancel_shipment(Origin::signed(2),1), Error::<Test>::UnauthorizedCaller);
+		assert_ok!(AssetTracking::cancel_shipment(Origin::signed(1),1));
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().status, ShipmentStatus::Cancelled);
+		assert_eq!(AssetTracking::shipment_commitment(1), None);
+		assert_eq!(AssetTracking::archival_queue(0), Some((1, 1)));
+		assert_eq!(Balances::reserved_balance(1), 0);
+
+		// Amending re-plans the route from the holder and reserves the extra leg
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(2,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_noop!(AssetTracking::amend_destination(Origin::signed(1),2,1), Error::<Test>::InvalidDestination);
+		assert_ok!(AssetTracking::amend_destination(Origin::signed(1),2,4));
+		System::assert_last_event(Event::AssetTracking(crate::Event::DestinationAmended(2,3,4)));
+		assert_eq!(AssetTracking::uid_to_shipment(2).unwrap().route.to_vec(), vec![1,2,3,4]);
+		assert_eq!(Balances::reserved_balance(1), 30);
+
+		// Afterwards the holder keeps the penalty out of the remaining fees
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),2,secret(1),Some(commitment(2,2)),bounded_vec![],None));
+		assert_ok!(AssetTracking::cancel_shipment(Origin::signed(1),2));
+		System::assert_last_event(Event::AssetTracking(crate::Event::ShipmentCancelled(2,2,15)));
+		assert_eq!(Balances::free_balance(2), 1_000_000 + 10 + 15);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert!(AssetTracking::shipments_held_by(&2).is_empty());
+	});
+}
+
+#[test]
+fn it_indexes_shipments_by_role() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,1)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(2,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(2),3,commitment(3,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+
+		// Pages follow storage order and continue after the last uid of the previous one
+		let mut created = AssetTracking::shipments_by(ShipmentRole::Creator,&1,None,10);
+		created.sort();
+		assert_eq!(created, vec![1,2]);
+		let first = AssetTracking::shipments_by(ShipmentRole::Destination,&3,None,1);
+		assert_eq!(first.len(), 1);
+		let second = AssetTracking::shipments_by(ShipmentRole::Destination,&3,Some(first[0]),1);
+		assert!(AssetTracking::shipments_by(ShipmentRole::Destination,&3,Some(second[0]),10).is_empty());
+		let mut bound_for_3 = [first, second].concat();
+		bound_for_3.sort();
+		assert_eq!(bound_for_3, vec![1,3]);
+		let mut held = AssetTracking::shipments_by(ShipmentRole::Holder,&1,None,10);
+		held.sort();
+		assert_eq!(held, vec![1,2]);
+
+		// Custody moves with handoffs, and packed shipments are listed under their parent
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+		assert_eq!(AssetTracking::shipments_by(ShipmentRole::Holder,&1,None,10), vec![2]);
+		assert_ok!(AssetTracking::pack_shipments(Origin::signed(2),1,bounded_vec![3]));
+		assert_eq!(AssetTracking::shipments_by(ShipmentRole::Holder,&2,None,10), vec![1]);
+		assert_eq!(AssetTracking::shipments_held_by(&2), vec![1,3]);
+
+		// Children packed in an arrived parent are held by its destination
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(3),1,secret(2),None,bounded_vec![],None));
+		assert!(AssetTracking::shipments_by(ShipmentRole::Holder,&2,None,10).is_empty());
+		assert_eq!(AssetTracking::shipments_held_by(&3), vec![3]);
+
+		// Pages never exceed MAX_SHIPMENTS_PAGE
+		for uid in 100..(300 + MAX_SHIPMENTS_PAGE as u64) {
+			ShipmentsByCreator::<Test>::insert(4, uid, ());
+		}
+		assert_eq!(AssetTracking::shipments_by(ShipmentRole::Creator,&4,None,u32::MAX).len(), MAX_SHIPMENTS_PAGE as usize);
+	});
+}
+
+#[test]
+fn it_archives_finalized_shipments_when_idle() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(2,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),None,bounded_vec![],None));
+		assert_ok!(AssetTracking::accept_shipment(Origin::signed(2),1));
+		System::set_block_number(11);
+		AssetTracking::on_initialize(11);
+		assert_eq!(AssetTracking::uid_to_shipment(2).unwrap().status, ShipmentStatus::Failed);
+
+		// Retained for 20 blocks after being finalized
+		let delivered = AssetTracking::uid_to_shipment(1).unwrap();
+		let records = AssetTracking::shipment_records(1);
+		assert_eq!(records.planned_arrivals.to_vec(), vec![11]);
+		AssetTracking::on_idle(20, u64::MAX);
+		assert!(AssetTracking::uid_to_shipment(1).is_some());
+		AssetTracking::on_idle(21, u64::MAX);
+		assert!(AssetTracking::uid_to_shipment(1).is_none());
+		assert!(AssetTracking::uid_to_shipment(2).is_some());
+
+		let record_hash = AssetTracking::archive_hash(&delivered, &records);
+		assert!(AssetTracking::planned_arrivals(1).is_empty());
+		assert_eq!(AssetTracking::archived_shipments(1), Some(record_hash));
+		System::assert_last_event(Event::AssetTracking(crate::Event::ShipmentArchived(1,record_hash)));
+		assert_eq!(AssetTracking::shipments_by(ShipmentRole::Creator,&1,None,10), vec![2]);
+
+		AssetTracking::on_idle(31, u64::MAX);
+		assert!(AssetTracking::uid_to_shipment(2).is_none());
+		assert_eq!(AssetTracking::archival_head(), AssetTracking::archival_tail());
+	});
+}
+
+#[test]
+fn it_optimises_routes_for_the_chosen_objective() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(1,5)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),4,bounded_vec![(2,1),(3,5)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),5,bounded_vec![(1,3),(4,3)]));
+		assert_ok!(AssetTracking::set_edge_duration(Origin::root(),1,3,Some(2)));
+		assert_ok!(AssetTracking::set_edge_duration(Origin::root(),3,4,Some(2)));
+		assert_ok!(AssetTracking::set_emission_factor(Origin::root(),TransportMode::Road,Some(10)));
+		assert_ok!(AssetTracking::set_emission_factor(Origin::root(),TransportMode::Rail,Some(1)));
+		assert_ok!(AssetTracking::set_emission_factor(Origin::root(),TransportMode::Sea,Some(1)));
+		let road = EdgeProfile { distance: 50, mode: TransportMode::Road };
+		let rail = EdgeProfile { distance: 100, mode: TransportMode::Rail };
+		let sea = EdgeProfile { distance: 10, mode: TransportMode::Sea };
+		for (node1, node2, profile) in [(1,2,road),(2,4,road),(1,3,rail),(3,4,rail),(1,5,sea),(5,4,sea)] {
+			assert_ok!(AssetTracking::set_edge_profile(Origin::root(),node1,node2,Some(profile)));
+		}
+		assert_noop!(AssetTracking::set_edge_profile(Origin::root(),1,4,Some(road)), Error::<Test>::NotNeighbours);
+
+		let route = |objective| AssetTracking::quote_route(&1, &4, &objective).unwrap().0.into_inner();
+		assert_eq!(route(RouteObjective::Cheapest), vec![1,2,4]);
+		assert_eq!(route(RouteObjective::Fastest), vec![1,3,4]);
+		assert_eq!(route(RouteObjective::Greenest), vec![1,5,4]);
+		assert_eq!(route(RouteObjective::Weighted { cost: 1, transit_time: 0, emissions: 1 }), vec![1,5,4]);
+		assert_noop!(AssetTracking::create_shipment(Origin::signed(1),4,commitment(1,1),bounded_vec![],None,None,
+		RouteObjective::Weighted { cost: 0, transit_time: 0, emissions: 0 }), Error::<Test>::InvalidObjective);
+
+		// The shipment keeps the metrics of its route, and pays for the costlier edges
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),4,commitment(1,1),bounded_vec![],None,None,RouteObjective::Fastest));
+		let shipment = AssetTracking::uid_to_shipment(1).unwrap();
+		assert_eq!(shipment.route.to_vec(), vec![1,3,4]);
+		assert_eq!((shipment.objective, shipment.metrics),
+		(RouteObjective::Fastest, RouteMetrics { cost: 10, transit_time: 4, emissions: 200 }));
+		assert_eq!(Balances::reserved_balance(1), 100);
+	});
+}
+
+#[test]
+fn it_bounds_the_edges_of_each_node() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		for node in 2..=9 {
+			assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),node,bounded_vec![(1,1)]));
+		}
+		assert_noop!(AssetTracking::create_new_transit_node(Origin::root(),10,bounded_vec![(1,1)]), Error::<Test>::TooManyEdges);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),10,bounded_vec![]));
+		assert_noop!(AssetTracking::update_neighbour(Origin::root(),1,10,1), Error::<Test>::TooManyEdges);
+		assert_noop!(AssetTracking::set_directed_edge(Origin::root(),1,10,Some(1)), Error::<Test>::TooManyEdges);
+
+		// Existing edges can still change, and only the full node is bounded
+		assert_ok!(AssetTracking::update_neighbour(Origin::root(),1,2,5));
+		assert_ok!(AssetTracking::set_directed_edge(Origin::root(),10,1,Some(1)));
+
+		// Closing an edge makes room for another
+		assert_ok!(AssetTracking::set_directed_edge(Origin::root(),1,9,None));
+		assert_ok!(AssetTracking::set_directed_edge(Origin::root(),1,10,Some(1)));
+	});
+}
+
+#[test]
+fn it_reroutes_shipments_when_edge_durations_change() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),4,bounded_vec![(2,1),(3,1)]));
+		for (node1, node2, duration) in [(1,2,2),(2,4,2),(1,3,5),(3,4,5)] {
+			assert_ok!(AssetTracking::set_edge_duration(Origin::root(),node1,node2,Some(duration)));
+		}
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),4,commitment(1,1),bounded_vec![],None,None,RouteObjective::Fastest));
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().route.to_vec(), vec![1,2,4]);
+
+		// Slowing down a lane of the fastest route moves the shipment to the other one
+		assert_ok!(AssetTracking::set_edge_duration(Origin::root(),1,2,Some(20)));
+		let shipment = AssetTracking::uid_to_shipment(1).unwrap();
+		assert_eq!(shipment.route.to_vec(), vec![1,3,4]);
+		assert_eq!(shipment.metrics, RouteMetrics { cost: 2, transit_time: 10, emissions: 0 });
+		assert_eq!(AssetTracking::planned_arrivals(1).to_vec(), vec![6,11]);
+		System::assert_has_event(Event::AssetTracking(crate::Event::ShipmentRerouted(1,bounded_vec![1,2,4],bounded_vec![1,3,4])));
+		System::assert_last_event(Event::AssetTracking(crate::Event::EdgeDurationSet(1,2,Some(20))));
+	});
+}
+
+#[test]
+fn it_plans_over_directed_edges() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),4,bounded_vec![(1,5),(3,5)]));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+
+		// A one-way lane is only planned in its direction
+		assert_ok!(AssetTracking::set_directed_edge(Origin::root(),3,2,None));
+		let route = |origin, dest| AssetTracking::quote_route(&origin, &dest, &RouteObjective::Cheapest).unwrap().0.into_inner();
+		assert_eq!(route(1,3), vec![1,2,3]);
+		assert_eq!(route(3,1), vec![3,4,1]);
+		assert_ok!(AssetTracking::set_edge_duration(Origin::root(),2,3,Some(4)));
+		let transit_time = |from, to| AssetTracking::edge(from, to).and_then(|edge| edge.transit_time);
+		assert_eq!((transit_time(2,3), transit_time(3,2)), (Some(4), None));
+
+		// Closing the other direction too reroutes shipments planned over it
+		assert_ok!(AssetTracking::set_directed_edge(Origin::root(),2,3,None));
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().route.to_vec(), vec![1,4,3]);
+		assert_eq!(transit_time(2,3), None);
+		assert_noop!(AssetTracking::set_directed_edge(Origin::root(),2,3,None), Error::<Test>::NotNeighbours);
+
+		// Each direction is priced on its own
+		assert_ok!(AssetTracking::set_directed_edge(Origin::root(),1,2,Some(20)));
+		let cost = |from, to| AssetTracking::edge(from, to).map(|edge| edge.cost);
+		assert_eq!((cost(1,2), cost(2,1)), (Some(20), Some(1)));
+		System::assert_last_event(Event::AssetTracking(crate::Event::DirectedEdgeSet(1,2,Some(20))));
+	});
+}
+
+#[test]
+fn it_accounts_leg_emissions_per_shipment_and_node() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,1)]));
+		assert_noop!(AssetTracking::set_emission_factor(Origin::signed(1),TransportMode::Road,Some(60)),
+		sp_runtime::DispatchError::BadOrigin);
+		assert_ok!(AssetTracking::set_emission_factor(Origin::root(),TransportMode::Road,Some(60)));
+		assert_ok!(AssetTracking::set_emission_factor(Origin::root(),TransportMode::Rail,Some(20)));
+		assert_ok!(AssetTracking::set_edge_profile(Origin::root(),1,2,Some(EdgeProfile { distance: 100, mode: TransportMode::Road })));
+		assert_ok!(AssetTracking::set_edge_profile(Origin::root(),2,3,Some(EdgeProfile { distance: 40, mode: TransportMode::Rail })));
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().metrics.emissions, 6_800);
+
+		// Each leg is charged to the node that carried it once completed
+		System::set_block_number(2);
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+		System::assert_has_event(Event::AssetTracking(crate::Event::LegEmissionsRecorded(1,0,1,6_000)));
+		System::set_block_number(5);
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(3),1,secret(2),None,bounded_vec![],None));
+		assert_eq!(AssetTracking::leg_emissions(1).into_inner(), vec![(0,6_000,2),(1,800,5)]);
+		assert_eq!(AssetTracking::shipment_emissions(1), 6_800);
+		assert_eq!((AssetTracking::node_emissions(1), AssetTracking::node_emissions(2)), (6_000, 800));
+
+		// Reports only cover legs completed within the block range
+		assert_eq!(AssetTracking::emissions_report(&1,0,10), (6_800, 6_000, vec![(1,6_800)], 5));
+		assert_eq!(AssetTracking::emissions_report(&1,3,10), (800, 0, vec![(1,800)], 5));
+		assert_eq!(AssetTracking::emissions_report(&1,2,4), (6_000, 6_000, vec![(1,6_000)], 4));
+		assert_eq!(AssetTracking::emissions_report(&2,0,10), (0, 800, vec![(1,800)], 5));
+		assert_eq!(AssetTracking::emissions_report(&3,0,10), (0, 0, vec![], 5));
+
+		// Long ranges are cut at MAX_REPORT_BLOCKS, to be continued from the block after
+		System::set_block_number(3 * MAX_REPORT_BLOCKS as u64);
+		let last = MAX_REPORT_BLOCKS as u64 + 1;
+		assert_eq!(AssetTracking::emissions_report(&1,2,u64::MAX), (6_800, 6_000, vec![(1,6_800)], last));
+		assert_eq!(AssetTracking::emissions_report(&1,last + 1,u64::MAX), (0, 0, vec![], last + MAX_REPORT_BLOCKS as u64));
+
+		// Archiving the shipment removes its ledger entries
+		assert_ok!(AssetTracking::accept_shipment(Origin::signed(3),1));
+		AssetTracking::on_idle(3 * MAX_REPORT_BLOCKS as u64 + 20, u64::MAX);
+		assert!(AssetTracking::uid_to_shipment(1).is_none());
+		assert_eq!(EmissionsLedger::<Test>::iter().count(), 0);
+		assert_eq!(AssetTracking::emissions_report(&1,2,u64::MAX), (0, 0, vec![], last));
+	});
+}
+
+#[test]
+fn it_spills_deadlines_of_crowded_blocks_over() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		// More legs due at block 11 than are expired per block
+		for uid in 1..=20 {
+			assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(uid,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		}
+		let failed = || (1..=20u64).filter(|uid| AssetTracking::uid_to_shipment(uid).unwrap().status == ShipmentStatus::Failed).count();
+
+		System::set_block_number(11);
+		AssetTracking::on_initialize(11);
+		assert_eq!(failed(), 16);
+		assert_eq!(AssetTracking::deadline_cursor(), 11);
+
+		// The rest are expired first thing in the next block
+		System::set_block_number(12);
+		AssetTracking::on_initialize(12);
+		assert_eq!(failed(), 20);
+		assert_eq!(AssetTracking::deadline_cursor(), 13);
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn it_spills_arrival_checks_of_crowded_blocks_over() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::set_edge_duration(Origin::root(),1,2,Some(4)));
+		// More legs planned to arrive at block 5 than are checked per block
+		for uid in 1..=20 {
+			assert_ok!(AssetTracking::create_shipment(Origin::signed(1),2,commitment(uid,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		}
+		let late = || System::events().iter()
+			.filter(|record| matches!(record.event, Event::AssetTracking(crate::Event::ShipmentLate(_,_,5))))
+			.count();
+
+		System::set_block_number(5);
+		AssetTracking::on_initialize(5);
+		assert_eq!(late(), 16);
+		assert_eq!(AssetTracking::arrival_cursor(), 5);
+
+		// The rest are reported first thing in the next block, at their planned arrival
+		System::set_block_number(6);
+		AssetTracking::on_initialize(6);
+		assert_eq!(late(), 20);
+		assert_eq!(AssetTracking::arrival_cursor(), 7);
+	});
+}
+
+#[test]
+fn it_queues_reroutes_beyond_the_block_limit() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),2,bounded_vec![(1,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),3,bounded_vec![(2,1)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),4,bounded_vec![(1,5),(3,5)]));
+		for uid in 1..=6u64 {
+			assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(uid,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		}
+		assert_eq!(ShipmentsByRouteNode::<Test>::iter_key_prefix(2).count(), 6);
+
+		// Only as many shipments as the limit are rerouted by the call itself
+		assert_ok!(AssetTracking::remove_transit_node(Origin::root(),2));
+		let rerouted = || (1..=6u64)
+			.filter(|uid| AssetTracking::uid_to_shipment(uid).unwrap().route.to_vec() == vec![1,4,3])
+			.count();
+		assert_eq!(rerouted(), 4);
+		assert_eq!(AssetTracking::reroute_head(), 0);
+
+		// The rest are picked up from the queue in the next block
+		System::set_block_number(2);
+		AssetTracking::on_initialize(2);
+		assert_eq!(rerouted(), 6);
+		assert_eq!((AssetTracking::reroute_head(), AssetTracking::reroute_tail()), (1, 1));
+		assert_eq!(AssetTracking::reroute_cursor(), None);
+		assert_eq!(ShipmentsByRouteNode::<Test>::iter_key_prefix(2).count(), 0);
+		assert_eq!(ShipmentsByRouteNode::<Test>::iter_key_prefix(4).count(), 6);
+	});
+}
+
+#[test]
+fn it_bonds_operator_nodes_from_the_operator() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		TransitNodeBond::set(1_000);
+		assert_ok!(AssetTracking::register_operator(Origin::root(),5));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::root(),1,bounded_vec![]));
+		assert_ok!(AssetTracking::set_node_operator(Origin::root(),1,Some(5)));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::signed(5),2,bounded_vec![(1,3)]));
+		assert_ok!(AssetTracking::create_new_transit_node(Origin::signed(5),3,bounded_vec![(2,5)]));
+		System::assert_has_event(Event::AssetTracking(crate::Event::TransitNodeBonded(2,5,1_000)));
+		assert_eq!((Balances::reserved_balance(2), Balances::reserved_balance(5)), (0, 2_000));
+		assert_eq!(AssetTracking::bonder_of(&1), 1);
+		assert_eq!(AssetTracking::bonder_of(&2), 5);
+
+		assert_ok!(AssetTracking::create_shipment(Origin::signed(1),3,commitment(1,1),bounded_vec![],None,None,RouteObjective::Cheapest));
+		assert_ok!(AssetTracking::update_shipment(Origin::signed(2),1,secret(1),Some(commitment(1,2)),bounded_vec![],None));
+
+		// The holder cannot leave the network with the shipment
+		assert_noop!(AssetTracking::remove_transit_node(Origin::signed(5),2), Error::<Test>::CustodyOutstanding);
+
+		// Stranding it by closing the holder's own lane costs the bond all the same
+		assert_ok!(AssetTracking::set_directed_edge(Origin::signed(5),2,3,None));
+		assert_eq!(AssetTracking::uid_to_shipment(1).unwrap().status, ShipmentStatus::Failed);
+		System::assert_has_event(Event::AssetTracking(crate::Event::TransitNodeSlashed(2,1,100)));
+		assert_eq!((AssetTracking::bonds(2), Balances::reserved_balance(5)), (Some(900), 1_900));
+
+		// Only the operator gets the bond back
+		assert_ok!(AssetTracking::remove_transit_node(Origin::signed(5),2));
+		System::set_block_number(21);
+		assert_noop!(AssetTracking::withdraw_bond(Origin::signed(2),2), Error::<Test>::NotBonder);
+		assert_ok!(AssetTracking::withdraw_bond(Origin::signed(5),2));
+		assert_eq!(Balances::reserved_balance(5), 1_000);
+		assert_eq!(AssetTracking::bonded_by(2), None);
+	});
+}
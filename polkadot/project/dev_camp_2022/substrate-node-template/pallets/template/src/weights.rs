@@ -0,0 +1,601 @@
+//! Weights for pallet_template
+//!
+//! Hand-maintained estimates laid out like the output of the Substrate benchmark CLI, not
+//! generated by it. Storage comments and read/write counts are worked out from the code
+//! paths the benchmarks in `benchmarking.rs` exercise; execution times are estimated rather
+//! than measured. The route search is weighed on its own by `route_search`, which the
+//! dispatchables searching for routes add at `MaxRouteSearchIterations`. Regenerate on
+//! reference hardware with:
+//!
+//! ./target/release/node-template benchmark pallet
+//! --chain=dev
+//! --execution=wasm
+//! --wasm-execution=compiled
+//! --pallet=pallet_template
+//! --extrinsic=*
+//! --steps=50
+//! --repeat=20
+//! --output=./pallets/template/src/weights.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_template.
+pub trait WeightInfo {
+	fn create_new_transit_node(n: u32, ) -> Weight;
+	fn update_neighbour() -> Weight;
+	fn set_directed_edge() -> Weight;
+	fn set_edge_duration() -> Weight;
+	fn set_edge_profile() -> Weight;
+	fn set_emission_factor() -> Weight;
+	fn remove_transit_node(n: u32, ) -> Weight;
+	fn reroute_shipment() -> Weight;
+	fn route_search(n: u32, ) -> Weight;
+	fn create_shipment() -> Weight;
+	fn update_shipment() -> Weight;
+	fn accept_shipment() -> Weight;
+	fn reject_shipment() -> Weight;
+	fn cancel_shipment() -> Weight;
+	fn amend_destination() -> Weight;
+	fn open_dispute() -> Weight;
+	fn resolve_dispute() -> Weight;
+	fn register_operator() -> Weight;
+	fn deregister_operator() -> Weight;
+	fn set_node_operator() -> Weight;
+	fn set_capacity() -> Weight;
+	fn withdraw_bond() -> Weight;
+	fn pack_shipments(n: u32, ) -> Weight;
+	fn unpack_shipment() -> Weight;
+	fn submit_telemetry() -> Weight;
+	fn submit_reading() -> Weight;
+}
+
+/// Weights for pallet_template using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: AssetTracking TransitNodes (r:1 w:1)
+	// Storage: AssetTracking Bonds (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: AssetTracking Unbonding (r:0 w:1)
+	// Storage: AssetTracking Edges (r:9 w:2)
+	// Storage: AssetTracking CounterForTransitNodes (r:1 w:1)
+	// Storage: AssetTracking RouteGeneration (r:1 w:1)
+	fn create_new_transit_node(n: u32, ) -> Weight {
+		(41_720_000 as Weight)
+			.saturating_add((6_410_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((9 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: AssetTracking TransitNodes (r:2 w:0)
+	// Storage: AssetTracking Edges (r:18 w:2)
+	// Storage: AssetTracking RouteGeneration (r:1 w:1)
+	// Storage: AssetTracking RerouteTail (r:1 w:1)
+	// Storage: AssetTracking RerouteQueue (r:0 w:1)
+	// Storage: AssetTracking RerouteHead (r:1 w:1)
+	// Storage: AssetTracking RerouteCursor (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:1 w:0)
+	fn update_neighbour() -> Weight {
+		(44_870_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(24 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	// Storage: AssetTracking TransitNodes (r:2 w:0)
+	// Storage: AssetTracking Edges (r:9 w:1)
+	// Storage: AssetTracking RouteGeneration (r:1 w:1)
+	// Storage: AssetTracking RerouteTail (r:1 w:1)
+	// Storage: AssetTracking RerouteQueue (r:0 w:1)
+	// Storage: AssetTracking RerouteHead (r:1 w:1)
+	// Storage: AssetTracking RerouteCursor (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:1 w:0)
+	fn set_directed_edge() -> Weight {
+		(39_260_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(15 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	// Storage: AssetTracking Edges (r:2 w:2)
+	// Storage: AssetTracking RouteGeneration (r:1 w:1)
+	// Storage: AssetTracking RerouteTail (r:1 w:1)
+	// Storage: AssetTracking RerouteQueue (r:0 w:1)
+	// Storage: AssetTracking RerouteHead (r:1 w:1)
+	// Storage: AssetTracking RerouteCursor (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:1 w:0)
+	fn set_edge_duration() -> Weight {
+		(31_480_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	// Storage: AssetTracking Edges (r:2 w:2)
+	// Storage: AssetTracking RouteGeneration (r:1 w:1)
+	// Storage: AssetTracking RerouteTail (r:1 w:1)
+	// Storage: AssetTracking RerouteQueue (r:0 w:1)
+	// Storage: AssetTracking RerouteHead (r:1 w:1)
+	// Storage: AssetTracking RerouteCursor (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:1 w:0)
+	fn set_edge_profile() -> Weight {
+		(32_050_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	// Storage: AssetTracking EmissionFactors (r:0 w:1)
+	fn set_emission_factor() -> Weight {
+		(9_840_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: AssetTracking TransitNodes (r:1 w:1)
+	// Storage: AssetTracking CustodyCount (r:1 w:0)
+	// Storage: AssetTracking Edges (r:0 w:1)
+	// Storage: AssetTracking CounterForTransitNodes (r:1 w:1)
+	// Storage: AssetTracking NodeOperator (r:0 w:1)
+	// Storage: AssetTracking Capacity (r:0 w:1)
+	// Storage: AssetTracking Bonds (r:1 w:0)
+	// Storage: AssetTracking Unbonding (r:0 w:1)
+	// Storage: AssetTracking RouteGeneration (r:1 w:1)
+	// Storage: AssetTracking RerouteTail (r:1 w:1)
+	// Storage: AssetTracking RerouteQueue (r:0 w:1)
+	// Storage: AssetTracking RerouteHead (r:1 w:1)
+	// Storage: AssetTracking RerouteCursor (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:1 w:0)
+	fn remove_transit_node(n: u32, ) -> Weight {
+		(44_630_000 as Weight)
+			.saturating_add((4_150_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(11 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: AssetTracking RerouteHead (r:1 w:1)
+	// Storage: AssetTracking RerouteTail (r:1 w:0)
+	// Storage: AssetTracking RerouteQueue (r:1 w:1)
+	// Storage: AssetTracking RerouteCursor (r:1 w:1)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:2 w:4)
+	// Storage: AssetTracking UIDToShipment (r:1 w:1)
+	// Storage: AssetTracking ParentOf (r:1 w:0)
+	// Storage: AssetTracking Edges (r:2 w:0)
+	// Storage: System Account (r:1 w:1)
+	// Storage: AssetTracking PlannedArrivals (r:1 w:1)
+	// Storage: AssetTracking CustodyCount (r:1 w:1)
+	// Storage: AssetTracking ShipmentsByHolder (r:0 w:1)
+	// Storage: AssetTracking InboundQueue (r:1 w:1)
+	// Storage: AssetTracking InboundCount (r:1 w:1)
+	// Storage: AssetTracking DeadlineCursor (r:1 w:0)
+	// Storage: AssetTracking Deadlines (r:0 w:1)
+	// Storage: AssetTracking ArrivalCursor (r:1 w:0)
+	// Storage: AssetTracking ArrivalChecks (r:0 w:1)
+	fn reroute_shipment() -> Weight {
+		(96_350_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(17 as Weight))
+			.saturating_add(T::DbWeight::get().writes(16 as Weight))
+	}
+	// Storage: AssetTracking EmissionFactors (r:1 w:0)
+	// Storage: AssetTracking Edges (r:10 w:0)
+	// Storage: AssetTracking Capacity (r:8 w:0)
+	// Storage: AssetTracking CustodyCount (r:8 w:0)
+	// Storage: AssetTracking InboundCount (r:8 w:0)
+	fn route_search(n: u32, ) -> Weight {
+		(11_360_000 as Weight)
+			.saturating_add((41_870_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().reads((33 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: AssetTracking Capacity (r:2 w:0)
+	// Storage: AssetTracking ShipmentUID (r:1 w:1)
+	// Storage: AssetTracking Edges (r:2 w:0)
+	// Storage: AssetTracking CustodyCount (r:1 w:1)
+	// Storage: AssetTracking InboundCount (r:1 w:1)
+	// Storage: AssetTracking EmissionFactors (r:1 w:0)
+	// Storage: System Account (r:1 w:1)
+	// Storage: AssetTracking UIDToShipment (r:1 w:1)
+	// Storage: AssetTracking PlannedArrivals (r:0 w:1)
+	// Storage: AssetTracking DeadlineCursor (r:1 w:0)
+	// Storage: AssetTracking Deadlines (r:0 w:1)
+	// Storage: AssetTracking ArrivalCursor (r:1 w:0)
+	// Storage: AssetTracking ArrivalChecks (r:0 w:1)
+	// Storage: AssetTracking UIDToCommitment (r:0 w:1)
+	// Storage: AssetTracking UIDToMetadata (r:0 w:1)
+	// Storage: AssetTracking UIDToConditions (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByCreator (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByDestination (r:0 w:1)
+	// Storage: AssetTracking ParentOf (r:1 w:0)
+	// Storage: AssetTracking ShipmentsByHolder (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:0 w:3)
+	// Storage: AssetTracking InboundQueue (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: AssetTracking CustodyLog (r:1 w:1)
+	fn create_shipment() -> Weight {
+		(141_900_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(16 as Weight))
+			.saturating_add(T::DbWeight::get().writes(19 as Weight))
+	}
+	// Storage: AssetTracking UIDToShipment (r:1 w:1)
+	// Storage: AssetTracking UIDToCommitment (r:1 w:1)
+	// Storage: AssetTracking Disputes (r:1 w:0)
+	// Storage: AssetTracking ParentOf (r:1 w:0)
+	// Storage: AssetTracking CustodyCount (r:2 w:2)
+	// Storage: AssetTracking ShipmentsByHolder (r:0 w:2)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:0 w:3)
+	// Storage: AssetTracking InboundQueue (r:2 w:2)
+	// Storage: AssetTracking InboundCount (r:2 w:2)
+	// Storage: System Account (r:2 w:2)
+	// Storage: AssetTracking Edges (r:1 w:0)
+	// Storage: AssetTracking LegEmissions (r:1 w:1)
+	// Storage: AssetTracking ShipmentEmissions (r:1 w:1)
+	// Storage: AssetTracking NodeEmissions (r:1 w:1)
+	// Storage: AssetTracking EmissionsLedger (r:2 w:2)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: AssetTracking CustodyLog (r:1 w:1)
+	// Storage: AssetTracking DeadlineCursor (r:1 w:0)
+	// Storage: AssetTracking Deadlines (r:0 w:1)
+	// Storage: AssetTracking PlannedArrivals (r:1 w:0)
+	// Storage: AssetTracking ArrivalCursor (r:1 w:0)
+	// Storage: AssetTracking ArrivalChecks (r:0 w:1)
+	fn update_shipment() -> Weight {
+		(96_480_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(23 as Weight))
+			.saturating_add(T::DbWeight::get().writes(23 as Weight))
+	}
+	// Storage: AssetTracking UIDToShipment (r:1 w:1)
+	// Storage: AssetTracking ArchivalTail (r:1 w:1)
+	// Storage: AssetTracking ArchivalQueue (r:0 w:1)
+	fn accept_shipment() -> Weight {
+		(19_870_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: AssetTracking UIDToShipment (r:2 w:2)
+	// Storage: AssetTracking ReturnOf (r:1 w:1)
+	// Storage: AssetTracking Children (r:1 w:0)
+	// Storage: AssetTracking UIDToMetadata (r:1 w:1)
+	// Storage: AssetTracking UIDToConditions (r:1 w:1)
+	// Storage: AssetTracking ShipmentUID (r:1 w:1)
+	// Storage: AssetTracking RouteGeneration (r:1 w:0)
+	// Storage: AssetTracking RouteVector (r:1 w:1)
+	// Storage: AssetTracking Edges (r:2 w:0)
+	// Storage: System Account (r:1 w:1)
+	// Storage: AssetTracking PlannedArrivals (r:0 w:1)
+	// Storage: AssetTracking DeadlineCursor (r:1 w:0)
+	// Storage: AssetTracking Deadlines (r:0 w:1)
+	// Storage: AssetTracking ArrivalCursor (r:1 w:0)
+	// Storage: AssetTracking ArrivalChecks (r:0 w:1)
+	// Storage: AssetTracking UIDToCommitment (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByCreator (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByDestination (r:0 w:1)
+	// Storage: AssetTracking ParentOf (r:1 w:0)
+	// Storage: AssetTracking CustodyCount (r:1 w:1)
+	// Storage: AssetTracking ShipmentsByHolder (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:0 w:2)
+	// Storage: AssetTracking InboundQueue (r:1 w:1)
+	// Storage: AssetTracking InboundCount (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: AssetTracking CustodyLog (r:1 w:1)
+	// Storage: AssetTracking Rejections (r:0 w:1)
+	// Storage: AssetTracking ArchivalTail (r:1 w:1)
+	// Storage: AssetTracking ArchivalQueue (r:0 w:1)
+	fn reject_shipment() -> Weight {
+		(138_600_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(21 as Weight))
+			.saturating_add(T::DbWeight::get().writes(24 as Weight))
+	}
+	// Storage: AssetTracking UIDToShipment (r:1 w:1)
+	// Storage: AssetTracking Disputes (r:1 w:0)
+	// Storage: AssetTracking ParentOf (r:1 w:0)
+	// Storage: AssetTracking Children (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: AssetTracking CustodyCount (r:1 w:1)
+	// Storage: AssetTracking ShipmentsByHolder (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:0 w:2)
+	// Storage: AssetTracking InboundQueue (r:1 w:1)
+	// Storage: AssetTracking InboundCount (r:1 w:1)
+	// Storage: AssetTracking UIDToCommitment (r:0 w:1)
+	// Storage: AssetTracking CustodyLog (r:0 w:1)
+	// Storage: AssetTracking ArchivalTail (r:1 w:1)
+	// Storage: AssetTracking ArchivalQueue (r:0 w:1)
+	fn cancel_shipment() -> Weight {
+		(49_310_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().writes(13 as Weight))
+	}
+	// Storage: AssetTracking UIDToShipment (r:1 w:1)
+	// Storage: AssetTracking Disputes (r:1 w:0)
+	// Storage: AssetTracking ParentOf (r:1 w:0)
+	// Storage: AssetTracking TransitNodes (r:1 w:0)
+	// Storage: AssetTracking Capacity (r:3 w:0)
+	// Storage: AssetTracking Edges (r:2 w:0)
+	// Storage: AssetTracking PlannedArrivals (r:1 w:1)
+	// Storage: AssetTracking CustodyCount (r:1 w:1)
+	// Storage: AssetTracking ShipmentsByHolder (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:0 w:4)
+	// Storage: AssetTracking InboundQueue (r:1 w:1)
+	// Storage: AssetTracking InboundCount (r:2 w:2)
+	// Storage: AssetTracking DeadlineCursor (r:1 w:0)
+	// Storage: AssetTracking Deadlines (r:0 w:1)
+	// Storage: AssetTracking ArrivalCursor (r:1 w:0)
+	// Storage: AssetTracking ArrivalChecks (r:0 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: AssetTracking ShipmentsByDestination (r:0 w:2)
+	fn amend_destination() -> Weight {
+		(101_200_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(17 as Weight))
+			.saturating_add(T::DbWeight::get().writes(16 as Weight))
+	}
+	// Storage: AssetTracking UIDToShipment (r:1 w:0)
+	// Storage: AssetTracking Disputes (r:1 w:1)
+	fn open_dispute() -> Weight {
+		(19_530_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: AssetTracking Disputes (r:1 w:1)
+	// Storage: AssetTracking UIDToShipment (r:1 w:1)
+	// Storage: AssetTracking ParentOf (r:1 w:1)
+	// Storage: AssetTracking CustodyCount (r:1 w:1)
+	// Storage: AssetTracking ShipmentsByHolder (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:0 w:2)
+	// Storage: AssetTracking InboundQueue (r:1 w:1)
+	// Storage: AssetTracking InboundCount (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: AssetTracking ArchivalTail (r:1 w:1)
+	// Storage: AssetTracking ArchivalQueue (r:0 w:1)
+	// Storage: AssetTracking UIDToCommitment (r:0 w:1)
+	// Storage: AssetTracking CustodyLog (r:0 w:1)
+	// Storage: AssetTracking Children (r:1 w:1)
+	fn resolve_dispute() -> Weight {
+		(61_950_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(15 as Weight))
+	}
+	// Storage: AssetTracking Operators (r:1 w:1)
+	fn register_operator() -> Weight {
+		(15_840_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: AssetTracking Operators (r:1 w:1)
+	fn deregister_operator() -> Weight {
+		(16_210_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: AssetTracking TransitNodes (r:1 w:0)
+	// Storage: AssetTracking Operators (r:1 w:0)
+	// Storage: AssetTracking NodeOperator (r:0 w:1)
+	fn set_node_operator() -> Weight {
+		(18_960_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: AssetTracking TransitNodes (r:1 w:0)
+	// Storage: AssetTracking Capacity (r:0 w:1)
+	fn set_capacity() -> Weight {
+		(15_940_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: AssetTracking Bonds (r:1 w:1)
+	// Storage: AssetTracking BondedBy (r:1 w:1)
+	// Storage: AssetTracking TransitNodes (r:1 w:0)
+	// Storage: AssetTracking Unbonding (r:1 w:1)
+	// Storage: AssetTracking CustodyCount (r:1 w:0)
+	// Storage: System Account (r:1 w:1)
+	fn withdraw_bond() -> Weight {
+		(37_120_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Storage: AssetTracking UIDToShipment (r:1 w:0)
+	// Storage: AssetTracking ParentOf (r:1 w:1)
+	// Storage: AssetTracking Children (r:1 w:1)
+	// Storage: AssetTracking Disputes (r:1 w:0)
+	// Storage: AssetTracking CustodyCount (r:1 w:1)
+	// Storage: AssetTracking ShipmentsByHolder (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:0 w:3)
+	// Storage: AssetTracking InboundQueue (r:1 w:1)
+	// Storage: AssetTracking InboundCount (r:1 w:1)
+	fn pack_shipments(n: u32, ) -> Weight {
+		(24_310_000 as Weight)
+			.saturating_add((13_260_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((5 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes((6 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: AssetTracking ParentOf (r:1 w:1)
+	// Storage: AssetTracking UIDToShipment (r:2 w:1)
+	// Storage: AssetTracking Edges (r:2 w:0)
+	// Storage: System Account (r:1 w:1)
+	// Storage: AssetTracking PlannedArrivals (r:1 w:1)
+	// Storage: AssetTracking DeadlineCursor (r:1 w:0)
+	// Storage: AssetTracking Deadlines (r:0 w:1)
+	// Storage: AssetTracking ArrivalCursor (r:1 w:0)
+	// Storage: AssetTracking ArrivalChecks (r:0 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: AssetTracking CustodyLog (r:1 w:1)
+	// Storage: AssetTracking UIDToCommitment (r:0 w:1)
+	// Storage: AssetTracking Children (r:1 w:1)
+	// Storage: AssetTracking CustodyCount (r:1 w:1)
+	// Storage: AssetTracking ShipmentsByHolder (r:0 w:1)
+	// Storage: AssetTracking ShipmentsByRouteNode (r:0 w:2)
+	// Storage: AssetTracking InboundQueue (r:1 w:1)
+	// Storage: AssetTracking InboundCount (r:1 w:1)
+	fn unpack_shipment() -> Weight {
+		(112_400_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(15 as Weight))
+			.saturating_add(T::DbWeight::get().writes(15 as Weight))
+	}
+	// Storage: AssetTracking UIDToShipment (r:1 w:0)
+	// Storage: AssetTracking ParentOf (r:1 w:0)
+	// Storage: AssetTracking UIDToConditions (r:1 w:0)
+	// Storage: AssetTracking NextTelemetryAt (r:1 w:1)
+	// Storage: AssetTracking Breaches (r:1 w:1)
+	// Storage: AssetTracking Bonds (r:1 w:1)
+	// Storage: AssetTracking BondedBy (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: AssetTracking LatestTelemetry (r:0 w:1)
+	fn submit_telemetry() -> Weight {
+		(54_280_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	// Storage: AssetTracking UIDToShipment (r:1 w:0)
+	// Storage: AssetTracking ParentOf (r:1 w:0)
+	// Storage: AssetTracking UIDToConditions (r:1 w:0)
+	// Storage: AssetTracking NextTelemetryAt (r:1 w:1)
+	// Storage: AssetTracking Breaches (r:1 w:1)
+	// Storage: AssetTracking Bonds (r:1 w:1)
+	// Storage: AssetTracking BondedBy (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	// Storage: AssetTracking LatestTelemetry (r:0 w:1)
+	fn submit_reading() -> Weight {
+		(51_540_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn create_new_transit_node(n: u32, ) -> Weight {
+		(41_720_000 as Weight)
+			.saturating_add((6_410_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((9 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(n as Weight)))
+	}
+	fn update_neighbour() -> Weight {
+		(44_870_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(24 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
+	fn set_directed_edge() -> Weight {
+		(39_260_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(15 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	fn set_edge_duration() -> Weight {
+		(31_480_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
+	fn set_edge_profile() -> Weight {
+		(32_050_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
+	fn set_emission_factor() -> Weight {
+		(9_840_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn remove_transit_node(n: u32, ) -> Weight {
+		(44_630_000 as Weight)
+			.saturating_add((4_150_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(11 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	fn reroute_shipment() -> Weight {
+		(96_350_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(17 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(16 as Weight))
+	}
+	fn route_search(n: u32, ) -> Weight {
+		(11_360_000 as Weight)
+			.saturating_add((41_870_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((33 as Weight).saturating_mul(n as Weight)))
+	}
+	fn create_shipment() -> Weight {
+		(141_900_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(16 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(19 as Weight))
+	}
+	fn update_shipment() -> Weight {
+		(96_480_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(23 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(23 as Weight))
+	}
+	fn accept_shipment() -> Weight {
+		(19_870_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn reject_shipment() -> Weight {
+		(138_600_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(21 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(24 as Weight))
+	}
+	fn cancel_shipment() -> Weight {
+		(49_310_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(13 as Weight))
+	}
+	fn amend_destination() -> Weight {
+		(101_200_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(17 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(16 as Weight))
+	}
+	fn open_dispute() -> Weight {
+		(19_530_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn resolve_dispute() -> Weight {
+		(61_950_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(15 as Weight))
+	}
+	fn register_operator() -> Weight {
+		(15_840_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn deregister_operator() -> Weight {
+		(16_210_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_node_operator() -> Weight {
+		(18_960_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_capacity() -> Weight {
+		(15_940_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn withdraw_bond() -> Weight {
+		(37_120_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	fn pack_shipments(n: u32, ) -> Weight {
+		(24_310_000 as Weight)
+			.saturating_add((13_260_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((5 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((6 as Weight).saturating_mul(n as Weight)))
+	}
+	fn unpack_shipment() -> Weight {
+		(112_400_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(15 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(15 as Weight))
+	}
+	fn submit_telemetry() -> Weight {
+		(54_280_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	fn submit_reading() -> Weight {
+		(51_540_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+}
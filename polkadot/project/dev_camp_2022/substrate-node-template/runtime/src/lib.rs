@@ -15,7 +15,7 @@ use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify},
-	transaction_validity::{TransactionSource, TransactionValidity},
+	transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature,
 };
 use sp_std::prelude::*;
@@ -26,7 +26,7 @@ use sp_version::RuntimeVersion;
 // A few exports that help ease life for downstream crates.
 pub use frame_support::{
 	construct_runtime, parameter_types,
-	traits::{ConstU128, ConstU32, ConstU8, KeyOwnerProofSystem, Randomness, StorageInfo},
+	traits::{ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem, Randomness, StorageInfo},
 	weights::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
 		IdentityFee, Weight,
@@ -99,10 +99,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 	state_version: 1,
 };
 
@@ -267,8 +267,46 @@ impl pallet_sudo::Config for Runtime {
 impl pallet_template::Config for Runtime {
 	type Event = Event;
 	type Currency = Balances;
-	type KeyRandomNess = RandomnessCollectiveFlip;
 	type MaxSize = frame_support::traits::ConstU32<100>;
+	// Searches and reroutes are weighed at these bounds, so that a change to the transit network
+	// rerouting `MaxReroutesPerBlock` shipments still fits a block
+	type MaxRouteSearchIterations = frame_support::traits::ConstU32<250>;
+	type MaxEdgesPerNode = ConstU32<8>;
+	type PricePerCostUnit = ConstU128<1_000_000>;
+	type LegTimeout = ConstU32<{ 6 * HOURS }>;
+	type MaxDeadlinesPerBlock = ConstU32<64>;
+	type MaxReroutesPerBlock = ConstU32<2>;
+	type ArbiterOrigin = frame_system::EnsureRoot<AccountId>;
+	type NetworkAdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type TransitNodeBond = ConstU128<1_000_000_000_000>;
+	type FailureSlash = ConstU128<100_000_000_000>;
+	type CancellationPenalty = ConstU128<10_000_000>;
+	type UnbondingPeriod = ConstU32<{ 7 * DAYS }>;
+	type RetentionPeriod = ConstU32<{ 30 * DAYS }>;
+	// Enough for a CIDv1 in base32
+	type MaxContentIdLen = ConstU32<64>;
+	type MaxMass = ConstU32<30_000_000>;
+	type MaxVolume = ConstU32<100_000_000>;
+	type MaxDeclaredValue = ConstU128<{ u128::MAX }>;
+	type MaxChildren = ConstU32<32>;
+	type AuthorityId = pallet_template::crypto::TelemetryAuthId;
+	type TelemetryInterval = ConstU32<{ 10 * MINUTES }>;
+	type TelemetryPriority = ConstU64<{ TransactionPriority::max_value() / 2 }>;
+	type MaxSealedSecretLen = ConstU32<256>;
+	type WeightInfo = pallet_template::weights::SubstrateWeight<Runtime>;
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	Call: From<C>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = UncheckedExtrinsic;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -319,6 +357,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	pallet_template::migrations::v1::MigrateToV1<Runtime>,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -332,7 +371,7 @@ mod benches {
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
 		[pallet_timestamp, Timestamp]
-		[pallet_template, TemplateModule]
+		[pallet_template, AssetTracking]
 	);
 }
 
@@ -467,6 +506,72 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_template_runtime_api::AssetTrackingApi<Block, AccountId, Balance, BlockNumber> for Runtime {
+		fn shipment(uid: u64) -> Option<pallet_template_runtime_api::ShipmentInfo<AccountId, Balance, BlockNumber>> {
+			AssetTracking::uid_to_shipment(uid).map(|shipment| {
+				let mut info: pallet_template_runtime_api::ShipmentInfo<_, _, _> = shipment.into();
+				info.metadata = AssetTracking::shipment_metadata(uid).map(Into::into);
+				info
+			})
+		}
+
+		fn shipments_held_by(account: AccountId) -> Vec<u64> {
+			AssetTracking::shipments_held_by(&account)
+		}
+
+		fn quote_route(
+			origin: AccountId,
+			destination: AccountId,
+			objective: pallet_template_runtime_api::RouteObjective,
+		) -> Option<pallet_template_runtime_api::RouteQuote<AccountId, Balance, BlockNumber>> {
+			AssetTracking::quote_route(&origin, &destination, &objective).map(|(route, metrics, fees)| {
+				pallet_template_runtime_api::RouteQuote {
+					route: route.into_inner(),
+					cost: metrics.cost,
+					fees,
+					transit_time: metrics.transit_time,
+					emissions: metrics.emissions,
+				}
+			})
+		}
+
+		fn transit_network() -> Vec<(AccountId, Vec<(AccountId, pallet_template_runtime_api::EdgeInfo<BlockNumber>)>)> {
+			AssetTracking::transit_network()
+				.into_iter()
+				.map(|(node, edges)| (node, edges.into_iter().map(|(neighbour, edge)| (neighbour, edge.into())).collect()))
+				.collect()
+		}
+
+		fn eta(uid: u64) -> Option<pallet_template_runtime_api::EtaInfo<BlockNumber>> {
+			AssetTracking::current_eta(uid).map(|expected_arrival| pallet_template_runtime_api::EtaInfo {
+				planned_arrivals: AssetTracking::planned_arrivals(uid).into_inner(),
+				expected_arrival,
+			})
+		}
+
+		fn inbound_shipments(transit_node: AccountId) -> Vec<(u64, BlockNumber)> {
+			AssetTracking::inbound_shipments(&transit_node)
+		}
+
+		fn shipments_by(
+			role: pallet_template_runtime_api::ShipmentRole,
+			account: AccountId,
+			start_after: Option<u64>,
+			limit: u32,
+		) -> Vec<u64> {
+			AssetTracking::shipments_by(role, &account, start_after, limit)
+		}
+
+		fn emissions_report(
+			account: AccountId,
+			from: BlockNumber,
+			to: BlockNumber,
+		) -> pallet_template_runtime_api::EmissionsReport<BlockNumber> {
+			let (as_creator, as_carrier, shipments, to) = AssetTracking::emissions_report(&account, from, to);
+			pallet_template_runtime_api::EmissionsReport { as_creator, as_carrier, shipments, to }
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (